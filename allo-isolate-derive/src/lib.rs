@@ -0,0 +1,243 @@
+#![deny(missing_docs, rust_2018_idioms)]
+
+//! Derive macros for [`allo-isolate`](https://docs.rs/allo-isolate).
+//!
+//! Use them through the `derive` feature of `allo-isolate` instead of
+//! depending on this crate directly.
+
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::{
+    parse_macro_input, spanned::Spanned, Data, DeriveInput, Field, Fields,
+    Index, LitInt,
+};
+
+/// Derives `IntoDart` and `IntoDartExceptPrimitive`.
+///
+/// - structs with fields are sent as a `List` of their fields, in the order
+///   they are declared in, the same way tuples are sent.
+/// - unit structs are sent as `null`.
+/// - enums are sent as a `List` holding the index of the variant followed by
+///   the fields of the variant.
+///
+/// Fields accept the following attributes:
+/// - `#[dart(skip)]`: the field is not sent at all.
+/// - `#[dart(position = N)]`: the field is sent at position `N` of the list,
+///   the other fields fill the remaining positions in declaration order.
+/// - `#[dart(zero_copy)]`: the field is wrapped in a `ZeroCopyBuffer` before
+///   being sent.
+///
+/// ```rust,ignore
+/// #[derive(allo_isolate::IntoDart)]
+/// struct Frame {
+///     #[dart(position = 1)]
+///     id: u64,
+///     #[dart(zero_copy)]
+///     pixels: Vec<u8>,
+///     #[dart(skip)]
+///     cache: Vec<u8>,
+/// }
+/// // sent as `[pixels, id]`
+/// ```
+#[proc_macro_derive(IntoDart, attributes(dart))]
+pub fn derive_into_dart(
+    input: proc_macro::TokenStream,
+) -> proc_macro::TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+fn expand(input: DeriveInput) -> syn::Result<TokenStream> {
+    let name = &input.ident;
+    let mut bounded = Vec::new();
+    let body = match &input.data {
+        Data::Struct(data) => {
+            let (pattern, values) = fields(&data.fields, &mut bounded)?;
+            match &data.fields {
+                Fields::Unit => quote! {
+                    ::allo_isolate::IntoDart::into_dart(())
+                },
+                _ => quote! {
+                    let #name #pattern = self;
+                    ::allo_isolate::IntoDart::into_dart(
+                        ::std::vec![#(#values),*]
+                    )
+                },
+            }
+        },
+        Data::Enum(data) => {
+            let arms = data
+                .variants
+                .iter()
+                .enumerate()
+                .map(|(index, variant)| {
+                    let ident = &variant.ident;
+                    let index = index as i32;
+                    let (pattern, values) =
+                        fields(&variant.fields, &mut bounded)?;
+                    Ok(quote! {
+                        Self::#ident #pattern => {
+                            ::allo_isolate::IntoDart::into_dart(::std::vec![
+                                ::allo_isolate::IntoDart::into_dart(#index),
+                                #(#values),*
+                            ])
+                        },
+                    })
+                })
+                .collect::<syn::Result<Vec<_>>>()?;
+            quote! {
+                match self {
+                    #(#arms)*
+                }
+            }
+        },
+        Data::Union(data) => {
+            return Err(syn::Error::new(
+                data.union_token.span(),
+                "IntoDart can not be derived for unions",
+            ))
+        },
+    };
+
+    let mut generics = input.generics.clone();
+    if generics.type_params().next().is_some() {
+        let where_clause = generics.make_where_clause();
+        for ty in bounded {
+            where_clause
+                .predicates
+                .push(syn::parse_quote!(#ty: ::allo_isolate::IntoDart));
+        }
+    }
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics ::allo_isolate::IntoDart for #name #ty_generics
+            #where_clause
+        {
+            fn into_dart(self) -> ::allo_isolate::ffi::DartCObject {
+                #body
+            }
+        }
+
+        impl #impl_generics ::allo_isolate::IntoDartExceptPrimitive
+            for #name #ty_generics #where_clause
+        {
+        }
+    })
+}
+
+#[derive(Default)]
+struct FieldAttrs {
+    skip: bool,
+    zero_copy: bool,
+    position: Option<(usize, proc_macro2::Span)>,
+}
+
+fn field_attrs(field: &Field) -> syn::Result<FieldAttrs> {
+    let mut attrs = FieldAttrs::default();
+    for attr in field.attrs.iter().filter(|a| a.path().is_ident("dart")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("skip") {
+                attrs.skip = true;
+            } else if meta.path.is_ident("zero_copy") {
+                attrs.zero_copy = true;
+            } else if meta.path.is_ident("position") {
+                let lit: LitInt = meta.value()?.parse()?;
+                attrs.position = Some((lit.base10_parse()?, lit.span()));
+            } else {
+                return Err(meta.error(
+                    "expected one of `skip`, `position = N` or `zero_copy`",
+                ));
+            }
+            Ok(())
+        })?;
+    }
+    if attrs.skip && (attrs.zero_copy || attrs.position.is_some()) {
+        return Err(syn::Error::new(
+            field.span(),
+            "a skipped field can not have other `dart` attributes",
+        ));
+    }
+    Ok(attrs)
+}
+
+/// Returns the pattern that binds the fields and the expressions that turn
+/// the sent fields into `DartCObject`s, in the order they are sent in.
+///
+/// The types of the sent fields are pushed to `bounded`.
+fn fields(
+    fields: &Fields,
+    bounded: &mut Vec<TokenStream>,
+) -> syn::Result<(TokenStream, Vec<TokenStream>)> {
+    let mut bindings = Vec::new();
+    let mut sent = Vec::new();
+    for (i, field) in fields.iter().enumerate() {
+        let attrs = field_attrs(field)?;
+        let binding = format_ident!("__field{}", i);
+        let member = match &field.ident {
+            Some(ident) => quote!(#ident),
+            None => {
+                let index = Index::from(i);
+                quote!(#index)
+            },
+        };
+        if attrs.skip {
+            bindings.push(quote!(#member: _));
+            continue;
+        }
+        bindings.push(quote!(#member: #binding));
+        let ty = &field.ty;
+        let value = if attrs.zero_copy {
+            bounded.push(quote!(::allo_isolate::ZeroCopyBuffer<#ty>));
+            quote! {
+                ::allo_isolate::IntoDart::into_dart(
+                    ::allo_isolate::ZeroCopyBuffer(#binding)
+                )
+            }
+        } else {
+            bounded.push(quote!(#ty));
+            quote!(::allo_isolate::IntoDart::into_dart(#binding))
+        };
+        sent.push((attrs.position, value));
+    }
+
+    let mut slots: Vec<Option<TokenStream>> = vec![None; sent.len()];
+    for (position, value) in &mut sent {
+        if let Some((position, span)) = *position {
+            match slots.get_mut(position) {
+                Some(slot @ None) => *slot = Some(value.clone()),
+                Some(Some(_)) => {
+                    return Err(syn::Error::new(
+                        span,
+                        "another field is already sent at this position",
+                    ))
+                },
+                None => {
+                    return Err(syn::Error::new(
+                        span,
+                        format!(
+                            "position out of range, only {} fields are sent",
+                            slots.len()
+                        ),
+                    ))
+                },
+            }
+        }
+    }
+    let mut rest = sent
+        .into_iter()
+        .filter(|(position, _)| position.is_none())
+        .map(|(_, value)| value);
+    let values = slots
+        .into_iter()
+        // there are exactly as many free slots as fields without a position
+        .map(|slot| slot.or_else(|| rest.next()).unwrap())
+        .collect();
+
+    let pattern = match fields {
+        Fields::Unit => quote!(),
+        _ => quote!({ #(#bindings),* }),
+    };
+    Ok((pattern, values))
+}
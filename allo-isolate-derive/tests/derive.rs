@@ -0,0 +1,133 @@
+use allo_isolate::{
+    ffi::{DartCObject, DartCObjectType, DartTypedDataType},
+    DartValue, FromDart, IntoDart,
+};
+
+#[derive(IntoDart)]
+struct Named {
+    id: u32,
+    name: String,
+}
+
+#[derive(IntoDart)]
+struct Tuple(i32, bool);
+
+#[derive(IntoDart)]
+struct Unit;
+
+#[derive(IntoDart)]
+struct Attributes {
+    #[dart(skip)]
+    _cache: Vec<u8>,
+    first: i32,
+    #[dart(position = 0)]
+    second: String,
+    #[dart(zero_copy)]
+    bytes: Vec<u8>,
+}
+
+#[derive(IntoDart)]
+struct Generic<T> {
+    items: Vec<T>,
+}
+
+#[derive(IntoDart)]
+enum Shape {
+    Empty,
+    Circle(f64),
+    Rect {
+        width: f64,
+        #[dart(skip)]
+        _label: String,
+        height: f64,
+    },
+}
+
+#[derive(IntoDart)]
+struct Nested {
+    shapes: Vec<Shape>,
+}
+
+fn decode<T: FromDart>(obj: DartCObject) -> T {
+    let decoded = T::from_dart(&obj).unwrap();
+    unsafe { allo_isolate::ffi::run_destructors(&obj) };
+    decoded
+}
+
+#[test]
+fn structs() {
+    let named = Named {
+        id: 7,
+        name: "seven".into(),
+    };
+    assert_eq!(
+        decode::<(u32, String)>(named.into_dart()),
+        (7, "seven".to_owned())
+    );
+    assert_eq!(
+        decode::<(i32, bool)>(Tuple(-1, true).into_dart()),
+        (-1, true)
+    );
+    assert_eq!(Unit.into_dart().ty, DartCObjectType::DartNull);
+    assert_eq!(
+        decode::<(Vec<String>,)>(
+            Generic {
+                items: vec!["a".to_owned()]
+            }
+            .into_dart()
+        ),
+        (vec!["a".to_owned()],)
+    );
+}
+
+#[test]
+fn attributes() {
+    let obj = Attributes {
+        _cache: vec![1, 2, 3],
+        first: 1,
+        second: "2".into(),
+        bytes: vec![3; 4],
+    }
+    .into_dart();
+    unsafe {
+        let items = std::slice::from_raw_parts(
+            obj.value.as_array.values,
+            obj.value.as_array.length as usize,
+        );
+        assert_eq!(items.len(), 3);
+        assert_eq!(String::from_dart(&*items[0]).unwrap(), "2");
+        assert_eq!(i32::from_dart(&*items[1]).unwrap(), 1);
+        let bytes = &*items[2];
+        assert_eq!(bytes.ty, DartCObjectType::DartExternalTypedData);
+        assert_eq!(
+            bytes.value.as_external_typed_data.ty,
+            DartTypedDataType::Uint8
+        );
+    }
+    assert_eq!(decode::<(String, i32, Vec<u8>)>(obj).2, vec![3; 4]);
+}
+
+#[test]
+fn enums() {
+    assert_eq!(decode::<(i32,)>(Shape::Empty.into_dart()), (0,));
+    assert_eq!(
+        decode::<(i32, f64)>(Shape::Circle(1.5).into_dart()),
+        (1, 1.5)
+    );
+    let rect = Shape::Rect {
+        width: 2.0,
+        _label: "skipped".into(),
+        height: 3.0,
+    };
+    assert_eq!(decode::<(i32, f64, f64)>(rect.into_dart()), (2, 2.0, 3.0));
+    let nested = Nested {
+        shapes: vec![Shape::Empty, Shape::Circle(1.0)],
+    };
+    assert_eq!(
+        decode::<DartValue>(nested.into_dart()),
+        DartValue::List(vec![DartValue::List(vec![
+            DartValue::List(vec![0.into()]),
+            DartValue::List(vec![1.into(), 1.0.into()]),
+        ])])
+    );
+}
@@ -0,0 +1,44 @@
+use allo_isolate::{ffi::DartCObject, IntoDart};
+use criterion::{
+    black_box, criterion_group, criterion_main, BenchmarkId, Criterion,
+};
+
+/// How arrays used to be built, with one allocation per item.
+fn boxed(strings: Vec<String>) {
+    let pointers: Vec<*mut DartCObject> = strings
+        .into_iter()
+        .map(IntoDart::into_dart)
+        .map(Box::new)
+        .map(Box::into_raw)
+        .collect();
+    for pointer in black_box(pointers) {
+        drop(unsafe { Box::from_raw(pointer) });
+    }
+}
+
+fn slab(strings: Vec<String>) {
+    drop(black_box(strings.into_dart()));
+}
+
+fn strings(count: usize) -> Vec<String> {
+    (0..count).map(|i| i.to_string()).collect()
+}
+
+fn criterion_benchmark(c: &mut Criterion) {
+    let mut group = c.benchmark_group("strings into dart array");
+    for i in [100, 1000, 100_000].iter() {
+        let input = strings(*i);
+        group.bench_with_input(
+            BenchmarkId::new("Box every item", i),
+            i,
+            |b, _| b.iter(|| boxed(black_box(input.clone()))),
+        );
+        group.bench_with_input(BenchmarkId::new("One slab", i), i, |b, _| {
+            b.iter(|| slab(black_box(input.clone())))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, criterion_benchmark);
+criterion_main!(benches);
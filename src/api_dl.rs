@@ -0,0 +1,220 @@
+//! The functions of the Dart API DL, that Dart hands over all at once.
+
+use std::{
+    ffi::{c_void, CStr},
+    fmt, mem,
+    sync::{atomic::Ordering, PoisonError, RwLock},
+};
+
+use crate::{
+    ffi::*, CLOSE_NATIVE_PORT, NEW_NATIVE_PORT, POST_COBJECT, POST_INTEGER,
+};
+
+static API_DL: RwLock<Option<DartApiDl>> = RwLock::new(None);
+
+macro_rules! api_dl {
+    ($($field:ident: $name:literal => $ty:ty,)+) => {
+        /// The functions of the Dart API DL, see [`initialize_api_dl`].
+        ///
+        /// A function is `None` when the VM is older than the function,
+        /// its minor version is lower than the one that introduced it.
+        #[derive(Copy, Clone, Debug)]
+        #[non_exhaustive]
+        pub struct DartApiDl {
+            /// The major version of the API of the VM.
+            pub major: i32,
+            /// The minor version of the API of the VM.
+            pub minor: i32,
+            $(
+                #[doc = concat!("`", $name, "`")]
+                pub $field: Option<$ty>,
+            )+
+        }
+
+        impl DartApiDl {
+            // some functions take nothing and return nothing already.
+            #[allow(clippy::useless_transmute)]
+            unsafe fn from_entries(
+                major: i32,
+                minor: i32,
+                entries: *const DartApiEntry,
+            ) -> Self {
+                Self {
+                    major,
+                    minor,
+                    $(
+                        $field: find(entries, $name).map(|function| {
+                            mem::transmute::<unsafe extern "C" fn(), $ty>(
+                                function,
+                            )
+                        }),
+                    )+
+                }
+            }
+        }
+    };
+}
+
+api_dl! {
+    post_cobject: "Dart_PostCObject" => DartPostCObjectFnType,
+    post_integer: "Dart_PostInteger" => DartPostIntegerFnType,
+    new_native_port: "Dart_NewNativePort" => DartNewNativePortFnType,
+    close_native_port: "Dart_CloseNativePort" => DartCloseNativePortFnType,
+    is_error: "Dart_IsError" => DartIsErrorFnType,
+    new_persistent_handle:
+        "Dart_NewPersistentHandle" => DartNewPersistentHandleFnType,
+    handle_from_persistent:
+        "Dart_HandleFromPersistent" => DartHandleFromPersistentFnType,
+    delete_persistent_handle:
+        "Dart_DeletePersistentHandle" => DartDeletePersistentHandleFnType,
+    new_weak_persistent_handle:
+        "Dart_NewWeakPersistentHandle" => DartNewWeakPersistentHandleFnType,
+    handle_from_weak_persistent:
+        "Dart_HandleFromWeakPersistent" => DartHandleFromWeakPersistentFnType,
+    delete_weak_persistent_handle:
+        "Dart_DeleteWeakPersistentHandle"
+            => DartDeleteWeakPersistentHandleFnType,
+    new_finalizable_handle:
+        "Dart_NewFinalizableHandle" => DartNewFinalizableHandleFnType,
+    delete_finalizable_handle:
+        "Dart_DeleteFinalizableHandle" => DartDeleteFinalizableHandleFnType,
+    update_finalizable_external_size:
+        "Dart_UpdateFinalizableExternalSize"
+            => DartUpdateFinalizableExternalSizeFnType,
+    enter_scope: "Dart_EnterScope" => DartEnterScopeFnType,
+    exit_scope: "Dart_ExitScope" => DartExitScopeFnType,
+}
+
+unsafe fn find(
+    mut entries: *const DartApiEntry,
+    name: &str,
+) -> Option<unsafe extern "C" fn()> {
+    while !(*entries).name.is_null() {
+        let entry = &*entries;
+        if CStr::from_ptr(entry.name).to_bytes() == name.as_bytes() {
+            return entry.function;
+        }
+        entries = entries.add(1);
+    }
+    None
+}
+
+/// The reason why the Dart API DL could not be initialized.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ApiDlError {
+    /// `NativeApi.initializeApiDLData` was null.
+    NullData,
+    /// The VM has a different major version of the API, its functions are
+    /// not compatible with this crate.
+    IncompatibleVersion {
+        /// The major version of the API of the VM.
+        major: i32,
+        /// The minor version of the API of the VM.
+        minor: i32,
+    },
+    /// The VM did not provide a function that this crate can not do without.
+    MissingFunction(&'static str),
+}
+
+impl fmt::Display for ApiDlError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NullData => {
+                f.write_str("NativeApi.initializeApiDLData is null")
+            },
+            Self::IncompatibleVersion { major, minor } => write!(
+                f,
+                "the Dart VM provides version {}.{} of the Dart API DL, \
+                 this crate needs version {}.x",
+                major, minor, DART_API_DL_MAJOR_VERSION
+            ),
+            Self::MissingFunction(name) => {
+                write!(f, "the Dart VM does not provide {}", name)
+            },
+        }
+    }
+}
+
+impl std::error::Error for ApiDlError {}
+
+/// Reads the functions of the Dart API DL out of
+/// `NativeApi.initializeApiDLData`, and stores them, so there is no need to
+/// call [`store_dart_post_cobject`](crate::store_dart_post_cobject) and the
+/// like anymore.
+///
+/// This is what [`store_dart_api_dl`] calls, it is only needed to know why
+/// the initialization failed.
+///
+/// #### Safety
+/// `data` must be null or come from `NativeApi.initializeApiDLData`.
+pub unsafe fn initialize_api_dl(
+    data: *mut c_void,
+) -> Result<DartApiDl, ApiDlError> {
+    let api = data
+        .cast::<DartApi>()
+        .as_ref()
+        .ok_or(ApiDlError::NullData)?;
+    // the functions of another major version do not have the same
+    // signatures, a lower minor version only lacks the newest functions.
+    if api.major != DART_API_DL_MAJOR_VERSION || api.functions.is_null() {
+        return Err(ApiDlError::IncompatibleVersion {
+            major: api.major,
+            minor: api.minor,
+        });
+    }
+    let api_dl = DartApiDl::from_entries(api.major, api.minor, api.functions);
+    let post_cobject = api_dl
+        .post_cobject
+        .ok_or(ApiDlError::MissingFunction("Dart_PostCObject"))?;
+    POST_COBJECT.store(Some(post_cobject), Ordering::Relaxed);
+    if let Some(post_integer) = api_dl.post_integer {
+        POST_INTEGER.store(Some(post_integer), Ordering::Relaxed);
+    }
+    if let (Some(new_native_port), Some(close_native_port)) =
+        (api_dl.new_native_port, api_dl.close_native_port)
+    {
+        NEW_NATIVE_PORT.store(Some(new_native_port), Ordering::Relaxed);
+        CLOSE_NATIVE_PORT.store(Some(close_native_port), Ordering::Relaxed);
+    }
+    *API_DL.write().unwrap_or_else(PoisonError::into_inner) = Some(api_dl);
+    Ok(api_dl)
+}
+
+/// Returns the functions of the Dart API DL, if [`store_dart_api_dl`] was
+/// called.
+pub fn dart_api_dl() -> Option<DartApiDl> {
+    *API_DL.read().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Stores the functions of the Dart API DL, this only should be called once
+/// at the start up of the Dart/Flutter Application, instead of
+/// [`store_dart_post_cobject`](crate::store_dart_post_cobject) and the other
+/// `store_dart_*` functions. it is exported and marked as `#[no_mangle]`.
+///
+/// Returns `0` on success and `-1` otherwise, like `Dart_InitializeApiDL`,
+/// see [`initialize_api_dl`] for the reason.
+///
+/// #### Safety
+/// `data` must be null or come from `NativeApi.initializeApiDLData`.
+///
+/// ### Example
+/// ```dart,ignore
+/// import 'dart:ffi';
+///
+/// // assumes that _dl is the `DynamicLibrary`
+/// final storeDartApiDl = _dl.lookupFunction<
+///     IntPtr Function(Pointer<Void>),
+///     int Function(Pointer<Void>)>('store_dart_api_dl');
+///
+/// if (storeDartApiDl(NativeApi.initializeApiDLData) != 0) {
+///   throw StateError('incompatible Dart API DL');
+/// }
+/// ```
+#[no_mangle]
+pub unsafe extern "C" fn store_dart_api_dl(data: *mut c_void) -> isize {
+    match initialize_api_dl(data) {
+        Ok(_) => 0,
+        Err(_) => -1,
+    }
+}
@@ -0,0 +1,145 @@
+use std::{
+    fmt, mem,
+    time::{Duration, Instant},
+};
+
+use crate::{ffi::DartCObject, IntoDart, Isolate};
+
+/// Queues messages and posts them to the [`Isolate`] together, as a single
+/// `List`, which wakes Dart up once per batch instead of once per message.
+///
+/// A batch is posted once it holds [`max_len`](Self::with_max_len) messages,
+/// once its oldest message waited for [`max_delay`](Self::with_max_delay), on
+/// [`BatchingIsolate::flush`], and when the [`BatchingIsolate`] is dropped.
+///
+/// There is no timer behind the batch, the delay is checked when a message
+/// is queued and on [`BatchingIsolate::poll_flush`], which has to be called
+/// by [`deadline`](Self::deadline) so that the last batch of a burst is not
+/// left waiting.
+///
+/// #### Example
+/// ```rust,ignore
+/// # use allo_isolate::Isolate;
+/// let mut telemetry = Isolate::new(port).batching();
+/// loop {
+///     let timeout = telemetry.deadline().map(|d| d - Instant::now());
+///     match events.recv_timeout(timeout.unwrap_or(Duration::MAX)) {
+///         Ok(event) => telemetry.post(event),
+///         Err(_) => telemetry.poll_flush(),
+///     };
+/// }
+/// // Dart receives lists of events.
+/// ```
+pub struct BatchingIsolate {
+    isolate: Isolate,
+    max_len: usize,
+    max_delay: Duration,
+    queue: Queue,
+    oldest: Option<Instant>,
+}
+
+struct Queue(Vec<DartCObject>);
+
+// what `IntoDart` makes owns everything it points to, and only holds objects
+// that Dart is allowed to free from any thread.
+unsafe impl Send for Queue {}
+
+impl BatchingIsolate {
+    /// Batches the messages to `isolate`, up to 64 of them and for up to
+    /// 16ms, about a frame.
+    pub const fn new(isolate: Isolate) -> Self {
+        Self {
+            isolate,
+            max_len: 64,
+            max_delay: Duration::from_millis(16),
+            queue: Queue(Vec::new()),
+            oldest: None,
+        }
+    }
+
+    /// Posts a batch as soon as it holds `max_len` messages.
+    pub const fn with_max_len(mut self, max_len: usize) -> Self {
+        self.max_len = max_len;
+        self
+    }
+
+    /// Posts a batch once its oldest message waited for `max_delay`.
+    pub const fn with_max_delay(mut self, max_delay: Duration) -> Self {
+        self.max_delay = max_delay;
+        self
+    }
+
+    /// Queues `msg`, and posts the batch if it is full or old enough.
+    ///
+    /// returns `false` if the batch had to be posted and could not be,
+    /// the messages of the batch are lost then.
+    pub fn post(&mut self, msg: impl IntoDart) -> bool {
+        let oldest = *self.oldest.get_or_insert_with(Instant::now);
+        self.queue.0.push(msg.into_dart());
+        if self.queue.0.len() >= self.max_len
+            || oldest.elapsed() >= self.max_delay
+        {
+            return self.flush();
+        }
+        true
+    }
+
+    /// Posts the batch if its oldest message waited for `max_delay`.
+    ///
+    /// returns `false` if the batch had to be posted and could not be,
+    /// the messages of the batch are lost then.
+    pub fn poll_flush(&mut self) -> bool {
+        match self.oldest {
+            Some(oldest) if oldest.elapsed() >= self.max_delay => self.flush(),
+            _ => true,
+        }
+    }
+
+    /// When the queued batch has to be posted, if any message is queued.
+    pub fn deadline(&self) -> Option<Instant> {
+        self.oldest.map(|oldest| oldest + self.max_delay)
+    }
+
+    /// Posts the queued messages, if any.
+    ///
+    /// returns `false` if they could not be posted, they are lost then.
+    pub fn flush(&mut self) -> bool {
+        self.oldest = None;
+        if self.queue.0.is_empty() {
+            return true;
+        }
+        self.isolate.post(mem::take(&mut self.queue.0))
+    }
+
+    /// The number of queued messages.
+    pub const fn len(&self) -> usize {
+        self.queue.0.len()
+    }
+
+    /// Whether no message is queued.
+    pub const fn is_empty(&self) -> bool {
+        self.queue.0.is_empty()
+    }
+
+    /// The [`Isolate`] that the batches are posted to.
+    pub const fn isolate(&self) -> Isolate {
+        self.isolate
+    }
+}
+
+impl fmt::Debug for BatchingIsolate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BatchingIsolate")
+            .field("isolate", &self.isolate)
+            .field("max_len", &self.max_len)
+            .field("max_delay", &self.max_delay)
+            .field("queued", &self.queue.0.len())
+            .finish()
+    }
+}
+
+impl Drop for BatchingIsolate {
+    fn drop(&mut self) {
+        self.flush();
+    }
+}
@@ -6,10 +6,13 @@
 //! as these are generally easier to work with.
 //! > see [timestamp_micros](https://docs.rs/chrono/0.4.20/chrono/naive/struct.NaiveDateTime.html?search=timestamp_micros#method.timestamp_micros)
 
+use chrono::TimeZone;
+
 use crate::{
     ffi::{DartCObject, DartHandleFinalizer, DartTypedDataType},
+    from_dart::{array, fixed, FromDartError},
     into_dart::{free_zero_copy_buffer_i64, DartTypedDataTypeTrait},
-    IntoDart,
+    FromDart, IntoDart,
 };
 
 impl IntoDart for chrono::DateTime<chrono::Utc> {
@@ -224,3 +227,137 @@ impl<const N: usize> IntoDart for [chrono::Duration; N] {
         vec.into_dart()
     }
 }
+
+fn naive_date_time_from_micros(
+    raw: i64,
+) -> Result<chrono::NaiveDateTime, FromDartError> {
+    chrono::NaiveDate::from_ymd_opt(1970, 1, 1)
+        .and_then(|epoch| epoch.and_hms_opt(0, 0, 0))
+        .and_then(|epoch| {
+            epoch.checked_add_signed(chrono::Duration::microseconds(raw))
+        })
+        .ok_or(FromDartError::OutOfRange)
+}
+
+fn naive_date_from_duration(
+    duration: chrono::Duration,
+) -> Result<chrono::NaiveDate, FromDartError> {
+    chrono::NaiveDate::MIN
+        .checked_add_signed(duration)
+        .ok_or(FromDartError::OutOfRange)
+}
+
+impl FromDart for chrono::DateTime<chrono::Utc> {
+    fn from_dart(obj: &DartCObject) -> Result<Self, FromDartError> {
+        let naive = naive_date_time_from_micros(i64::from_dart(obj)?)?;
+        Ok(chrono::Utc.from_utc_datetime(&naive))
+    }
+}
+
+impl FromDart for chrono::DateTime<chrono::Local> {
+    fn from_dart(obj: &DartCObject) -> Result<Self, FromDartError> {
+        let naive = naive_date_time_from_micros(i64::from_dart(obj)?)?;
+        Ok(chrono::Local.from_utc_datetime(&naive))
+    }
+}
+
+impl FromDart for chrono::NaiveDate {
+    fn from_dart(obj: &DartCObject) -> Result<Self, FromDartError> {
+        naive_date_from_duration(chrono::Duration::from_dart(obj)?)
+    }
+}
+
+impl FromDart for chrono::NaiveDateTime {
+    fn from_dart(obj: &DartCObject) -> Result<Self, FromDartError> {
+        naive_date_time_from_micros(i64::from_dart(obj)?)
+    }
+}
+
+impl FromDart for chrono::Duration {
+    /// `null` is what an overflowing [chrono::Duration::num_microseconds]
+    /// was sent as, so it is reported as [FromDartError::OutOfRange].
+    fn from_dart(obj: &DartCObject) -> Result<Self, FromDartError> {
+        Option::<i64>::from_dart(obj)?
+            .map(chrono::Duration::microseconds)
+            .ok_or(FromDartError::OutOfRange)
+    }
+}
+
+impl FromDart for Vec<chrono::DateTime<chrono::Utc>> {
+    fn from_dart(obj: &DartCObject) -> Result<Self, FromDartError> {
+        Vec::<i64>::from_dart(obj)?
+            .into_iter()
+            .map(|raw| {
+                naive_date_time_from_micros(raw)
+                    .map(|naive| chrono::Utc.from_utc_datetime(&naive))
+            })
+            .collect()
+    }
+}
+
+impl<const N: usize> FromDart for [chrono::DateTime<chrono::Utc>; N] {
+    fn from_dart(obj: &DartCObject) -> Result<Self, FromDartError> {
+        fixed(Vec::from_dart(obj)?)
+    }
+}
+
+impl FromDart for Vec<chrono::DateTime<chrono::Local>> {
+    fn from_dart(obj: &DartCObject) -> Result<Self, FromDartError> {
+        Vec::<i64>::from_dart(obj)?
+            .into_iter()
+            .map(|raw| {
+                naive_date_time_from_micros(raw)
+                    .map(|naive| chrono::Local.from_utc_datetime(&naive))
+            })
+            .collect()
+    }
+}
+
+impl<const N: usize> FromDart for [chrono::DateTime<chrono::Local>; N] {
+    fn from_dart(obj: &DartCObject) -> Result<Self, FromDartError> {
+        fixed(Vec::from_dart(obj)?)
+    }
+}
+
+impl FromDart for Vec<chrono::NaiveDate> {
+    fn from_dart(obj: &DartCObject) -> Result<Self, FromDartError> {
+        Vec::<chrono::Duration>::from_dart(obj)?
+            .into_iter()
+            .map(naive_date_from_duration)
+            .collect()
+    }
+}
+
+impl<const N: usize> FromDart for [chrono::NaiveDate; N] {
+    fn from_dart(obj: &DartCObject) -> Result<Self, FromDartError> {
+        fixed(Vec::from_dart(obj)?)
+    }
+}
+
+impl FromDart for Vec<chrono::NaiveDateTime> {
+    fn from_dart(obj: &DartCObject) -> Result<Self, FromDartError> {
+        Vec::<i64>::from_dart(obj)?
+            .into_iter()
+            .map(naive_date_time_from_micros)
+            .collect()
+    }
+}
+
+impl<const N: usize> FromDart for [chrono::NaiveDateTime; N] {
+    fn from_dart(obj: &DartCObject) -> Result<Self, FromDartError> {
+        fixed(Vec::from_dart(obj)?)
+    }
+}
+
+impl FromDart for Vec<chrono::Duration> {
+    /// sent as a `List` of nullable ints, see [chrono::Duration]
+    fn from_dart(obj: &DartCObject) -> Result<Self, FromDartError> {
+        array(obj)?.map(chrono::Duration::from_dart).collect()
+    }
+}
+
+impl<const N: usize> FromDart for [chrono::Duration; N] {
+    fn from_dart(obj: &DartCObject) -> Result<Self, FromDartError> {
+        fixed(Vec::from_dart(obj)?)
+    }
+}
@@ -1,7 +1,10 @@
 //! A FFI Compatible Array for Dart
 
-use core::slice;
-use std::mem::ManuallyDrop;
+use std::{
+    fmt,
+    mem::ManuallyDrop,
+    ptr::{self, NonNull},
+};
 
 use ffi::{DartCObject, DartCObjectType, DartCObjectValue, DartNativeArray};
 
@@ -9,9 +12,21 @@ use crate::{ffi, IntoDart};
 
 /// A wrapper around a list of `DartCObject` that will be dropped after been
 /// sent to dart vm.
-#[derive(Debug, Clone)]
+///
+/// The items live next to each other in a single slab, and the pointers that
+/// Dart reads point into it, so an array costs two allocations whatever its
+/// length.
 pub struct DartArray {
-    inner: Box<[*mut DartCObject]>,
+    pointers: Box<[*mut DartCObject]>,
+    items: Box<[DartCObject]>,
+}
+
+impl fmt::Debug for DartArray {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DartArray")
+            .field("len", &self.items.len())
+            .finish_non_exhaustive()
+    }
 }
 
 impl<I, T: IntoDart> From<I> for DartArray
@@ -19,25 +34,23 @@ where
     I: Iterator<Item = T>,
 {
     fn from(iter: I) -> Self {
-        let vec: Vec<_> = iter
-            // convert them to dart objects
-            .map(IntoDart::into_dart)
-            // box them to be transferred to dart
-            .map(Box::new)
-            // as pointers
-            .map(Box::into_raw)
-            // then collect everything
+        // convert them to dart objects, all in one slab
+        let mut items: Box<[DartCObject]> =
+            iter.map(IntoDart::into_dart).collect();
+        let pointers = items
+            .iter_mut()
+            .map(|item| item as *mut DartCObject)
             .collect();
-        let inner = vec.into_boxed_slice();
-        Self { inner }
+        Self { pointers, items }
     }
 }
 
 impl IntoDart for DartArray {
     fn into_dart(self) -> ffi::DartCObject {
         let mut s = ManuallyDrop::new(self);
-        // we drop vec when DartCObject get dropped
-        let (data, len) = (s.inner.as_mut_ptr(), s.inner.len());
+        // we drop both the pointers and the slab when DartCObject get
+        // dropped, the slab is found again through the first pointer
+        let (data, len) = (s.pointers.as_mut_ptr(), s.pointers.len());
 
         let array = DartNativeArray {
             length: len as isize,
@@ -52,21 +65,17 @@ impl IntoDart for DartArray {
 
 impl From<DartNativeArray> for DartArray {
     fn from(arr: DartNativeArray) -> Self {
-        let inner = unsafe {
-            let slice =
-                slice::from_raw_parts_mut(arr.values, arr.length as usize);
-            Box::from_raw(slice)
+        let len = arr.length as usize;
+        let pointers = unsafe {
+            Box::from_raw(ptr::slice_from_raw_parts_mut(arr.values, len))
         };
-        Self { inner }
-    }
-}
-
-impl Drop for DartArray {
-    fn drop(&mut self) {
-        for v in self.inner.iter() {
-            unsafe {
-                let _ = Box::from_raw(*v);
-            }
-        }
+        // the first pointer is the start of the slab
+        let start = pointers
+            .first()
+            .copied()
+            .unwrap_or(NonNull::dangling().as_ptr());
+        let items =
+            unsafe { Box::from_raw(ptr::slice_from_raw_parts_mut(start, len)) };
+        Self { pointers, items }
     }
 }
@@ -0,0 +1,239 @@
+//! Zero copy typed data whose memory is kept alive by an owner other than a
+//! `Vec`, like a reference counted buffer or a memory mapped file.
+//!
+//! Dart can write to external typed data, so buffers that might be shared
+//! with Rust are sent as unmodifiable external typed data.
+
+use std::{cell::Cell, ffi::c_void, marker::PhantomData, mem, sync::Arc};
+
+use crate::{
+    ffi::*,
+    into_dart::{
+        visit_dart_typed_data_type, DartTypedDataTypeTrait,
+        DartTypedDataTypeVisitor,
+    },
+    IntoDart, IntoDartExceptPrimitive,
+};
+
+/// Hands the elements that `owner` holds to Dart as external typed data of
+/// type `ty`, `owner` is dropped when Dart finalizes it.
+///
+/// Dart may write to the elements, `owner` must be their only owner.
+pub(crate) fn owner_to_dart_native_external_typed_data<O, T>(
+    owner: O,
+    ty: DartTypedDataType,
+) -> DartCObject
+where
+    O: AsRef<[T]> + Send + 'static,
+{
+    external_typed_data(owner, ty, DartCObjectType::DartExternalTypedData)
+}
+
+/// Like [`owner_to_dart_native_external_typed_data`], for elements that Dart
+/// must not write to, because they are shared or read-only.
+pub(crate) fn owner_to_dart_native_unmodifiable_external_typed_data<O, T>(
+    owner: O,
+    ty: DartTypedDataType,
+) -> DartCObject
+where
+    O: AsRef<[T]> + Send + 'static,
+{
+    external_typed_data(
+        owner,
+        ty,
+        DartCObjectType::DartUnmodifiableExternalTypedData,
+    )
+}
+
+fn external_typed_data<O, T>(
+    owner: O,
+    ty: DartTypedDataType,
+    external: DartCObjectType,
+) -> DartCObject
+where
+    O: AsRef<[T]> + Send + 'static,
+{
+    // the elements must not move anymore once they are handed over.
+    let owner = Box::new(owner);
+    let elements = (*owner).as_ref();
+    if elements.is_empty() {
+        return DartCObject {
+            ty: DartCObjectType::DartTypedData,
+            value: DartCObjectValue {
+                as_typed_data: DartNativeTypedData {
+                    ty,
+                    length: 0,
+                    values: std::ptr::null_mut(),
+                },
+            },
+        };
+    }
+    // `T` can be bigger than the elements of `ty`, like a `Uuid` is 16 bytes.
+    let length = mem::size_of_val(elements) / element_size(ty);
+    let data = elements.as_ptr() as *mut u8;
+    DartCObject {
+        ty: external,
+        value: DartCObjectValue {
+            as_external_typed_data: DartNativeExternalTypedData {
+                ty,
+                length: length as isize,
+                data,
+                peer: Box::into_raw(owner).cast(),
+                callback: drop_owner::<O>,
+            },
+        },
+    }
+}
+
+/// The size of the elements of typed data of type `ty`.
+fn element_size(ty: DartTypedDataType) -> usize {
+    struct Size(Cell<usize>);
+    impl DartTypedDataTypeVisitor for Size {
+        fn visit<T: DartTypedDataTypeTrait>(&self) {
+            self.0.set(mem::size_of::<T>());
+        }
+    }
+
+    let size = Size(Cell::new(0));
+    visit_dart_typed_data_type(ty, &size);
+    size.0.get()
+}
+
+unsafe extern "C" fn drop_owner<O>(
+    _isolate_callback_data: *mut c_void,
+    peer: *mut c_void,
+) {
+    drop(Box::from_raw(peer.cast::<O>()));
+}
+
+/// The buffer is shared with Dart, and released once both sides dropped it.
+/// Dart receives an unmodifiable list.
+impl<T> IntoDart for ZeroCopyBuffer<Arc<[T]>>
+where
+    T: DartTypedDataTypeTrait + Send + Sync + 'static,
+{
+    fn into_dart(self) -> DartCObject {
+        owner_to_dart_native_unmodifiable_external_typed_data(
+            self.0,
+            T::dart_typed_data_type(),
+        )
+    }
+}
+
+impl<T> IntoDartExceptPrimitive for ZeroCopyBuffer<Arc<[T]>> where
+    T: DartTypedDataTypeTrait + Send + Sync + 'static
+{
+}
+
+impl<T> IntoDart for ZeroCopyBuffer<Box<[T]>>
+where
+    T: DartTypedDataTypeTrait + Send + 'static,
+{
+    fn into_dart(self) -> DartCObject {
+        owner_to_dart_native_external_typed_data(
+            self.0,
+            T::dart_typed_data_type(),
+        )
+    }
+}
+
+impl<T> IntoDartExceptPrimitive for ZeroCopyBuffer<Box<[T]>> where
+    T: DartTypedDataTypeTrait + Send + 'static
+{
+}
+
+/// The buffer is shared with Dart, and released once both sides dropped it.
+/// Dart receives an unmodifiable `Uint8List`.
+#[cfg(feature = "bytes")]
+impl IntoDart for ZeroCopyBuffer<bytes::Bytes> {
+    fn into_dart(self) -> DartCObject {
+        owner_to_dart_native_unmodifiable_external_typed_data(
+            self.0,
+            DartTypedDataType::Uint8,
+        )
+    }
+}
+
+#[cfg(feature = "bytes")]
+impl IntoDartExceptPrimitive for ZeroCopyBuffer<bytes::Bytes> {}
+
+/// Sends the elements of any owner to Dart as external typed data, without
+/// copying them. The owner is dropped once Dart finalizes the typed data.
+///
+/// Anything that exposes a slice of elements can be the owner, for example
+/// the buffer of an ndarray, a `SmallVec` or memory from a custom allocator.
+/// The owner might not expect its elements to change, or hold read-only
+/// memory, so Dart receives an unmodifiable list.
+///
+/// #### Example
+/// ```rust,ignore
+/// # use allo_isolate::{ExternalTypedData, Isolate};
+/// let samples: SmallVec<[f32; 64]> = record();
+/// isolate.post(ExternalTypedData::new(samples)); // a Float32List
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct ExternalTypedData<O, T> {
+    owner: O,
+    _elements: PhantomData<fn() -> T>,
+}
+
+/// Sends the bytes of any owner to Dart as an unmodifiable `Uint8List`, see
+/// [`ExternalTypedData`].
+///
+/// #### Example
+/// ```rust,ignore
+/// # use allo_isolate::{ExternalBuffer, Isolate};
+/// let asset = unsafe { memmap2::Mmap::map(&File::open("atlas.bin")?)? };
+/// isolate.post(ExternalBuffer::new(asset));
+/// ```
+pub type ExternalBuffer<O> = ExternalTypedData<O, u8>;
+
+impl<O, T> ExternalTypedData<O, T>
+where
+    O: AsRef<[T]> + Send + 'static,
+    T: DartTypedDataTypeTrait,
+{
+    /// Wraps the owner of the elements.
+    pub const fn new(owner: O) -> Self {
+        Self {
+            owner,
+            _elements: PhantomData,
+        }
+    }
+
+    /// Returns the owner, if it was not sent after all.
+    pub fn into_inner(self) -> O {
+        self.owner
+    }
+}
+
+impl<O, T> IntoDart for ExternalTypedData<O, T>
+where
+    O: AsRef<[T]> + Send + 'static,
+    T: DartTypedDataTypeTrait,
+{
+    fn into_dart(self) -> DartCObject {
+        owner_to_dart_native_unmodifiable_external_typed_data(
+            self.owner,
+            T::dart_typed_data_type(),
+        )
+    }
+}
+
+impl<O, T> IntoDartExceptPrimitive for ExternalTypedData<O, T>
+where
+    O: AsRef<[T]> + Send + 'static,
+    T: DartTypedDataTypeTrait,
+{
+}
+
+/// The file stays mapped until Dart finalizes the list, which is unmodifiable.
+#[cfg(feature = "memmap2")]
+impl IntoDart for ZeroCopyBuffer<memmap2::Mmap> {
+    fn into_dart(self) -> DartCObject {
+        ExternalBuffer::new(self.0).into_dart()
+    }
+}
+
+#[cfg(feature = "memmap2")]
+impl IntoDartExceptPrimitive for ZeroCopyBuffer<memmap2::Mmap> {}
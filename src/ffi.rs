@@ -16,6 +16,10 @@ use crate::{
 /// A port is used to send or receive inter-isolate messages
 pub type DartPort = i64;
 
+/// `ILLEGAL_PORT` is a port number guaranteed never to be associated with a
+/// valid port.
+pub const ILLEGAL_PORT: DartPort = 0;
+
 #[repr(i32)]
 #[derive(Copy, Clone, PartialEq, Debug)]
 pub enum DartTypedDataType {
@@ -31,8 +35,10 @@ pub enum DartTypedDataType {
     Uint64 = 9,
     Float32 = 10,
     Float64 = 11,
-    Float32x4 = 12,
-    Invalid = 13,
+    Int32x4 = 12,
+    Float32x4 = 13,
+    Float64x2 = 14,
+    Invalid = 15,
 }
 
 /// A Dart_CObject is used for representing Dart objects as native C
@@ -51,7 +57,8 @@ pub enum DartTypedDataType {
 /// the caller. The ownership of data for kExternalTyped is passed to the VM on
 /// message send and returned when the VM invokes the
 /// Dart_WeakPersistentHandleFinalizer callback; a non-NULL callback must be
-/// provided.
+/// provided. kUnmodifiableExternalTypedData is the same as kExternalTypedData,
+/// but Dart receives an unmodifiable view of the data.
 ///
 /// https://github.com/dart-lang/sdk/blob/main/runtime/include/dart_native_api.h
 #[repr(i32)]
@@ -70,9 +77,15 @@ pub enum DartCObjectType {
     DartCapability = 10,
     DartNativePointer = 11,
     DartUnsupported = 12,
-    DartNumberOfTypes = 13,
+    DartUnmodifiableExternalTypedData = 13,
+    DartNumberOfTypes = 14,
 }
 
+/// Dropping a `DartCObject` frees what it points to, the way [`IntoDart`]
+/// allocated it, so a `DartArray` built by hand must follow the layout
+/// described on [`DartNativeArray`].
+///
+/// [`IntoDart`]: crate::IntoDart
 #[allow(missing_debug_implementations)]
 #[repr(C)]
 pub struct DartCObject {
@@ -111,6 +124,12 @@ pub struct DartNativeCapability {
     pub id: i64,
 }
 
+/// The items of a `DartArray`.
+///
+/// When a [`DartCObject`] holding an array is dropped, `values` must be a
+/// `Box<[*mut DartCObject]>` of `length` pointers, and the items a single
+/// `Box<[DartCObject]>` of `length` items, where `values[i]` points to the
+/// `i`th item. Items that were boxed one by one can not be dropped this way.
 #[repr(C)]
 #[derive(Debug, Copy, Clone)]
 pub struct DartNativeArray {
@@ -150,6 +169,9 @@ pub type DartHandleFinalizer =
 
 /// Wrapping a Vec<u8> in this tuple struct will allow into_dart()
 /// to send it as a DartNativeExternalTypedData buffer with no copy overhead
+///
+/// Shared buffers like `Arc<[T]>` can be wrapped too, Dart must not write to
+/// those since Rust might still read them.
 #[derive(Debug, Clone)]
 pub struct ZeroCopyBuffer<T>(pub T);
 
@@ -169,6 +191,127 @@ pub struct ZeroCopyBuffer<T>(pub T);
 pub type DartPostCObjectFnType =
     unsafe extern "C" fn(port_id: DartPort, message: *mut DartCObject) -> bool;
 
+/// A native message handler.
+///
+/// This handler is associated with a native port by calling
+/// `Dart_NewNativePort`.
+///
+/// The message received is decoded into the message structure. The
+/// lifetime of the message data is controlled by the caller. All the
+/// data references from the message are allocated by the caller and
+/// will be reclaimed when returning to it.
+pub type DartNativeMessageHandler =
+    unsafe extern "C" fn(dest_port_id: DartPort, message: *mut DartCObject);
+
+///  Creates a new native port. When messages are received on this
+///  native port, then they will be dispatched to the provided native
+///  message handler.
+///
+///  `name` The name of this port in debugging messages.
+///  `handler` The C handler to run when messages arrive on the port.
+///  `handle_concurrently` Is it okay to process requests on this
+///                        native port concurrently?
+///
+///  return If successful, returns the port id for the native port. In
+///    case of error, returns `ILLEGAL_PORT`.
+pub type DartNewNativePortFnType = unsafe extern "C" fn(
+    name: *const raw::c_char,
+    handler: DartNativeMessageHandler,
+    handle_concurrently: bool,
+) -> DartPort;
+
+///  Closes the native port with the given id.
+///
+///  The port must have been allocated by a call to `Dart_NewNativePort`.
+///
+///  `native_port_id` The id of the native port to close.
+///
+///  return Returns true if the port was closed successfully.
+pub type DartCloseNativePortFnType =
+    unsafe extern "C" fn(native_port_id: DartPort) -> bool;
+
+///  Posts an integer on some port, a lot cheaper than posting it as a
+///  `Dart_CObject`.
+///
+///  `port_id` The destination port.
+///  `message` The message to send.
+///
+///  return true if the message was posted.
+pub type DartPostIntegerFnType =
+    unsafe extern "C" fn(port_id: DartPort, message: i64) -> bool;
+
+/// An object of the Dart heap, only valid in the scope it was created in.
+pub type DartHandle = *mut c_void;
+
+/// A handle that keeps an object of the Dart heap alive until it is deleted.
+pub type DartPersistentHandle = *mut c_void;
+
+/// A handle to an object of the Dart heap that does not keep it alive.
+pub type DartWeakPersistentHandle = *mut c_void;
+
+/// A handle that runs a finalizer once its object is collected.
+pub type DartFinalizableHandle = *mut c_void;
+
+pub type DartNewPersistentHandleFnType =
+    unsafe extern "C" fn(object: DartHandle) -> DartPersistentHandle;
+pub type DartHandleFromPersistentFnType =
+    unsafe extern "C" fn(object: DartPersistentHandle) -> DartHandle;
+pub type DartDeletePersistentHandleFnType =
+    unsafe extern "C" fn(object: DartPersistentHandle);
+pub type DartNewWeakPersistentHandleFnType =
+    unsafe extern "C" fn(
+        object: DartHandle,
+        peer: *mut c_void,
+        external_allocation_size: isize,
+        callback: DartHandleFinalizer,
+    ) -> DartWeakPersistentHandle;
+pub type DartHandleFromWeakPersistentFnType =
+    unsafe extern "C" fn(object: DartWeakPersistentHandle) -> DartHandle;
+pub type DartDeleteWeakPersistentHandleFnType =
+    unsafe extern "C" fn(object: DartWeakPersistentHandle);
+pub type DartNewFinalizableHandleFnType =
+    unsafe extern "C" fn(
+        object: DartHandle,
+        peer: *mut c_void,
+        external_allocation_size: isize,
+        callback: DartHandleFinalizer,
+    ) -> DartFinalizableHandle;
+pub type DartDeleteFinalizableHandleFnType = unsafe extern "C" fn(
+    object: DartFinalizableHandle,
+    strong_ref_to_object: DartHandle,
+);
+pub type DartUpdateFinalizableExternalSizeFnType = unsafe extern "C" fn(
+    object: DartFinalizableHandle,
+    strong_ref_to_object: DartHandle,
+    external_allocation_size: isize,
+);
+pub type DartIsErrorFnType = unsafe extern "C" fn(handle: DartHandle) -> bool;
+pub type DartEnterScopeFnType = unsafe extern "C" fn();
+pub type DartExitScopeFnType = unsafe extern "C" fn();
+
+/// The major version of the Dart API DL that this crate is built against, the
+/// VM must have the same one, see `dart_version.h`.
+pub const DART_API_DL_MAJOR_VERSION: i32 = 2;
+
+/// What `NativeApi.initializeApiDLData` points to.
+///
+/// https://github.com/dart-lang/sdk/blob/main/runtime/include/dart_api_dl.c
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct DartApi {
+    pub major: raw::c_int,
+    pub minor: raw::c_int,
+    /// Terminated by an entry whose `name` is null.
+    pub functions: *const DartApiEntry,
+}
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct DartApiEntry {
+    pub name: *const raw::c_char,
+    pub function: Option<unsafe extern "C" fn()>,
+}
+
 impl Drop for DartCObject {
     fn drop(&mut self) {
         match self.ty {
@@ -207,15 +350,17 @@ impl Drop for DartCObject {
             | DartCObjectType::DartDouble => {
                 // do nothing, since they are primitive types
             },
-            DartCObjectType::DartExternalTypedData => {
+            DartCObjectType::DartExternalTypedData
+            | DartCObjectType::DartUnmodifiableExternalTypedData => {
                 // do NOT free any memory here
                 // see https://github.com/sunshine-protocol/allo-isolate/issues/7
             },
-            DartCObjectType::DartSendPort
-            | DartCObjectType::DartCapability
-            | DartCObjectType::DartUnsupported
+            DartCObjectType::DartSendPort | DartCObjectType::DartCapability => {
+                // do nothing, they only hold ids
+            },
+            DartCObjectType::DartUnsupported
             | DartCObjectType::DartNumberOfTypes => {
-                // not sure what to do here
+                // never created on the Rust side
             },
             DartCObjectType::DartNativePointer => {
                 // do not free the memory here, this will be done when the
@@ -230,12 +375,18 @@ impl Drop for DartCObject {
 pub unsafe fn run_destructors(obj: &DartCObject) {
     use DartCObjectType::*;
     match obj.ty {
-        DartExternalTypedData => unsafe {
+        DartExternalTypedData | DartUnmodifiableExternalTypedData => unsafe {
             (obj.value.as_external_typed_data.callback)(
                 obj.value.as_external_typed_data.data as *mut c_void,
                 obj.value.as_external_typed_data.peer,
             )
         },
+        DartNativePointer => unsafe {
+            (obj.value.as_native_pointer.callback)(
+                std::ptr::null_mut(),
+                obj.value.as_native_pointer.ptr as *mut c_void,
+            )
+        },
         DartArray => {
             let items = unsafe {
                 std::slice::from_raw_parts_mut(
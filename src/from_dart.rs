@@ -0,0 +1,503 @@
+//! The inverse of [`IntoDart`](crate::IntoDart), decodes a [`DartCObject`]
+//! graph (for example a message that Dart posted to a native port) back into
+//! Rust values.
+//!
+//! Every conversion here accepts what the matching `IntoDart` conversion
+//! produces, so a value that goes through `into_dart` and then `from_dart`
+//! comes back the same.
+
+use std::{
+    collections::{HashMap, HashSet},
+    ffi::{CStr, CString},
+    fmt,
+    hash::Hash,
+};
+
+use crate::{ffi::*, into_dart::DartTypedDataTypeTrait};
+
+/// A trait to convert Dart Types that were received from the isolate back
+/// into Rust types.
+///
+/// The [`DartCObject`] is only borrowed, since received messages are owned by
+/// the Dart VM and only live as long as the message handler runs.
+pub trait FromDart: Sized {
+    /// Performs the conversion.
+    ///
+    /// `obj` is trusted to be a well formed object graph, as produced by the
+    /// Dart VM or by [`IntoDart`](crate::IntoDart).
+    fn from_dart(obj: &DartCObject) -> Result<Self, FromDartError>;
+}
+
+/// A trait that is [`FromDart`] and is also not a primitive type, it mirrors
+/// [`IntoDartExceptPrimitive`](crate::IntoDartExceptPrimitive) so that
+/// [`Vec<i32>`] is decoded from an `Int32List` and not from a `List<int>`.
+pub trait FromDartExceptPrimitive: FromDart {}
+
+/// The error returned when a [`DartCObject`] can not be decoded into the
+/// requested Rust type.
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[non_exhaustive]
+pub enum FromDartError {
+    /// The object has a different type than the one expected.
+    UnexpectedType {
+        /// A short description of what was expected.
+        expected: &'static str,
+        /// The type of the received object.
+        found: DartCObjectType,
+    },
+    /// The object is typed data, but of a different element type.
+    UnexpectedTypedDataType {
+        /// The element type that was expected.
+        expected: DartTypedDataType,
+        /// The element type of the received typed data.
+        found: DartTypedDataType,
+    },
+    /// The number does not fit into the requested Rust type.
+    OutOfRange,
+    /// The string is not valid UTF-8.
+    InvalidUtf8,
+    /// The string does not hold a number that could be parsed.
+    InvalidNumber,
+    /// The list or typed data does not have the expected length.
+    LengthMismatch {
+        /// The expected length.
+        expected: usize,
+        /// The length of the received object.
+        found: usize,
+    },
+}
+
+impl fmt::Display for FromDartError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnexpectedType { expected, found } => {
+                write!(f, "expected {}, found {:?}", expected, found)
+            },
+            Self::UnexpectedTypedDataType { expected, found } => write!(
+                f,
+                "expected typed data of {:?}, found {:?}",
+                expected, found
+            ),
+            Self::OutOfRange => f.write_str("number out of range"),
+            Self::InvalidUtf8 => f.write_str("string is not valid UTF-8"),
+            Self::InvalidNumber => {
+                f.write_str("string does not hold a valid number")
+            },
+            Self::LengthMismatch { expected, found } => {
+                write!(f, "expected length {}, found {}", expected, found)
+            },
+        }
+    }
+}
+
+impl std::error::Error for FromDartError {}
+
+const fn unexpected(
+    expected: &'static str,
+    obj: &DartCObject,
+) -> FromDartError {
+    FromDartError::UnexpectedType {
+        expected,
+        found: obj.ty,
+    }
+}
+
+/// Reads an integer, no matter if it was sent as `int32` or `int64`.
+pub(crate) const fn int(obj: &DartCObject) -> Result<i64, FromDartError> {
+    match obj.ty {
+        DartCObjectType::DartInt32 => Ok(unsafe { obj.value.as_int32 } as i64),
+        DartCObjectType::DartInt64 => Ok(unsafe { obj.value.as_int64 }),
+        _ => Err(unexpected("an int", obj)),
+    }
+}
+
+/// Borrows the string of a `DartString`.
+pub(crate) fn str(obj: &DartCObject) -> Result<&str, FromDartError> {
+    c_str(obj)?.to_str().map_err(|_| FromDartError::InvalidUtf8)
+}
+
+const fn c_str(obj: &DartCObject) -> Result<&CStr, FromDartError> {
+    match obj.ty {
+        DartCObjectType::DartString => {
+            Ok(unsafe { CStr::from_ptr(obj.value.as_string) })
+        },
+        _ => Err(unexpected("a String", obj)),
+    }
+}
+
+/// Borrows the items of a `DartArray`.
+pub(crate) fn array(
+    obj: &DartCObject,
+) -> Result<impl ExactSizeIterator<Item = &DartCObject>, FromDartError> {
+    match obj.ty {
+        DartCObjectType::DartArray => {
+            let array = unsafe { obj.value.as_array };
+            let items = if array.length == 0 || array.values.is_null() {
+                &[][..]
+            } else {
+                unsafe {
+                    std::slice::from_raw_parts(
+                        array.values,
+                        array.length as usize,
+                    )
+                }
+            };
+            Ok(items.iter().map(|item| unsafe { &**item }))
+        },
+        _ => Err(unexpected("a List", obj)),
+    }
+}
+
+/// Borrows the elements of a `DartTypedData` or of external typed data,
+/// checking that they hold elements of type `T`.
+pub(crate) fn typed_data<T: DartTypedDataTypeTrait>(
+    obj: &DartCObject,
+) -> Result<&[T], FromDartError> {
+    typed_data_of(obj, T::dart_typed_data_type())
+}
+
+/// Like [`typed_data`], for typed data of type `expected` that holds `T`s,
+/// like a `Uint8ClampedList` that holds `u8`s.
+pub(crate) fn typed_data_of<T>(
+    obj: &DartCObject,
+    expected: DartTypedDataType,
+) -> Result<&[T], FromDartError> {
+    let (ty, length, values) = match obj.ty {
+        DartCObjectType::DartTypedData => {
+            let data = unsafe { obj.value.as_typed_data };
+            (data.ty, data.length, data.values)
+        },
+        DartCObjectType::DartExternalTypedData
+        | DartCObjectType::DartUnmodifiableExternalTypedData => {
+            let data = unsafe { obj.value.as_external_typed_data };
+            (data.ty, data.length, data.data)
+        },
+        _ => return Err(unexpected("typed data", obj)),
+    };
+    if ty != expected {
+        return Err(FromDartError::UnexpectedTypedDataType {
+            expected,
+            found: ty,
+        });
+    }
+    if length == 0 || values.is_null() {
+        return Ok(&[]);
+    }
+    Ok(unsafe { std::slice::from_raw_parts(values as *const T, length as _) })
+}
+
+fn list<T: FromDart>(obj: &DartCObject) -> Result<Vec<T>, FromDartError> {
+    array(obj)?.map(T::from_dart).collect()
+}
+
+/// Turns a decoded `Vec` into an array of exactly `N` items.
+pub(crate) fn fixed<T, const N: usize>(
+    vec: Vec<T>,
+) -> Result<[T; N], FromDartError> {
+    let found = vec.len();
+    vec.try_into()
+        .map_err(|_| FromDartError::LengthMismatch { expected: N, found })
+}
+
+impl FromDart for () {
+    fn from_dart(obj: &DartCObject) -> Result<Self, FromDartError> {
+        match obj.ty {
+            DartCObjectType::DartNull => Ok(()),
+            _ => Err(unexpected("null", obj)),
+        }
+    }
+}
+
+impl FromDart for bool {
+    fn from_dart(obj: &DartCObject) -> Result<Self, FromDartError> {
+        match obj.ty {
+            DartCObjectType::DartBool => Ok(unsafe { obj.value.as_bool }),
+            _ => Err(unexpected("a bool", obj)),
+        }
+    }
+}
+
+impl FromDartExceptPrimitive for bool {}
+
+impl FromDart for i64 {
+    fn from_dart(obj: &DartCObject) -> Result<Self, FromDartError> {
+        int(obj)
+    }
+}
+
+impl FromDart for f64 {
+    fn from_dart(obj: &DartCObject) -> Result<Self, FromDartError> {
+        match obj.ty {
+            DartCObjectType::DartDouble => Ok(unsafe { obj.value.as_double }),
+            _ => int(obj).map(|v| v as f64),
+        }
+    }
+}
+
+impl FromDart for f32 {
+    fn from_dart(obj: &DartCObject) -> Result<Self, FromDartError> {
+        f64::from_dart(obj).map(|v| v as f32)
+    }
+}
+
+macro_rules! from_dart_for_checked_int {
+    ($($rust_type:ident)+) => {$(
+        impl FromDart for $rust_type {
+            fn from_dart(obj: &DartCObject) -> Result<Self, FromDartError> {
+                $rust_type::try_from(int(obj)?)
+                    .map_err(|_| FromDartError::OutOfRange)
+            }
+        }
+    )+};
+}
+
+from_dart_for_checked_int!(i8 i16 i32 u8 u16 u32);
+
+// `u64` is sent as the `i64` with the same bits, so we undo just that.
+impl FromDart for u64 {
+    fn from_dart(obj: &DartCObject) -> Result<Self, FromDartError> {
+        int(obj).map(|v| v as u64)
+    }
+}
+
+#[cfg(target_pointer_width = "64")]
+impl FromDart for usize {
+    fn from_dart(obj: &DartCObject) -> Result<Self, FromDartError> {
+        u64::from_dart(obj).map(|v| v as usize)
+    }
+}
+
+#[cfg(target_pointer_width = "32")]
+impl FromDart for usize {
+    fn from_dart(obj: &DartCObject) -> Result<Self, FromDartError> {
+        i32::from_dart(obj).map(|v| v as u32 as usize)
+    }
+}
+
+impl FromDart for isize {
+    fn from_dart(obj: &DartCObject) -> Result<Self, FromDartError> {
+        isize::try_from(int(obj)?).map_err(|_| FromDartError::OutOfRange)
+    }
+}
+
+impl FromDart for i128 {
+    fn from_dart(obj: &DartCObject) -> Result<Self, FromDartError> {
+        str(obj)?.parse().map_err(|_| FromDartError::InvalidNumber)
+    }
+}
+
+impl FromDart for u128 {
+    fn from_dart(obj: &DartCObject) -> Result<Self, FromDartError> {
+        str(obj)?.parse().map_err(|_| FromDartError::InvalidNumber)
+    }
+}
+
+impl FromDart for char {
+    fn from_dart(obj: &DartCObject) -> Result<Self, FromDartError> {
+        char::from_u32(u32::from_dart(obj)?).ok_or(FromDartError::OutOfRange)
+    }
+}
+
+impl FromDart for String {
+    fn from_dart(obj: &DartCObject) -> Result<Self, FromDartError> {
+        str(obj).map(ToOwned::to_owned)
+    }
+}
+
+impl FromDartExceptPrimitive for String {}
+
+impl FromDart for CString {
+    fn from_dart(obj: &DartCObject) -> Result<Self, FromDartError> {
+        c_str(obj).map(ToOwned::to_owned)
+    }
+}
+
+impl FromDartExceptPrimitive for CString {}
+
+macro_rules! from_dart_for_typed_data {
+    ($($rust_type:ident)+) => {$(
+        /// Decoded from typed data (external or not), or from a `List` of
+        /// numbers.
+        impl FromDart for Vec<$rust_type> {
+            fn from_dart(obj: &DartCObject) -> Result<Self, FromDartError> {
+                match obj.ty {
+                    DartCObjectType::DartArray => list(obj),
+                    _ => typed_data::<$rust_type>(obj).map(<[_]>::to_vec),
+                }
+            }
+        }
+
+        impl FromDartExceptPrimitive for Vec<$rust_type> {}
+
+        impl<const N: usize> FromDart for [$rust_type; N] {
+            fn from_dart(obj: &DartCObject) -> Result<Self, FromDartError> {
+                fixed(Vec::from_dart(obj)?)
+            }
+        }
+
+        impl FromDart for ZeroCopyBuffer<Vec<$rust_type>> {
+            fn from_dart(obj: &DartCObject) -> Result<Self, FromDartError> {
+                Vec::from_dart(obj).map(ZeroCopyBuffer)
+            }
+        }
+
+        impl<const N: usize> FromDart for ZeroCopyBuffer<[$rust_type; N]> {
+            fn from_dart(obj: &DartCObject) -> Result<Self, FromDartError> {
+                <[$rust_type; N]>::from_dart(obj).map(ZeroCopyBuffer)
+            }
+        }
+    )+};
+}
+
+from_dart_for_typed_data!(i8 u8 i16 u16 i32 u32 i64 u64 f32 f64);
+
+macro_rules! from_dart_for_typed_data_set {
+    ($($rust_type:ident)+) => {$(
+        impl FromDart for HashSet<$rust_type> {
+            fn from_dart(obj: &DartCObject) -> Result<Self, FromDartError> {
+                Vec::<$rust_type>::from_dart(obj)
+                    .map(|vec| vec.into_iter().collect())
+            }
+        }
+    )+};
+}
+
+from_dart_for_typed_data_set!(i8 u8 i16 u16 i32 u32 i64 u64);
+
+macro_rules! isize_usize {
+    ($rust_type:ident, $delegate_target_type:ident) => {
+        impl FromDart for Vec<$rust_type> {
+            fn from_dart(obj: &DartCObject) -> Result<Self, FromDartError> {
+                Vec::<$delegate_target_type>::from_dart(obj)?
+                    .into_iter()
+                    .map(|x| {
+                        $rust_type::try_from(x)
+                            .map_err(|_| FromDartError::OutOfRange)
+                    })
+                    .collect()
+            }
+        }
+
+        impl FromDartExceptPrimitive for Vec<$rust_type> {}
+
+        impl<const N: usize> FromDart for [$rust_type; N] {
+            fn from_dart(obj: &DartCObject) -> Result<Self, FromDartError> {
+                fixed(Vec::from_dart(obj)?)
+            }
+        }
+
+        impl FromDart for ZeroCopyBuffer<Vec<$rust_type>> {
+            fn from_dart(obj: &DartCObject) -> Result<Self, FromDartError> {
+                Vec::from_dart(obj).map(ZeroCopyBuffer)
+            }
+        }
+    };
+}
+
+isize_usize!(isize, i64);
+isize_usize!(usize, u64);
+
+impl<T> FromDart for Vec<T>
+where
+    T: FromDartExceptPrimitive,
+{
+    fn from_dart(obj: &DartCObject) -> Result<Self, FromDartError> {
+        list(obj)
+    }
+}
+
+impl<T> FromDartExceptPrimitive for Vec<T> where T: FromDartExceptPrimitive {}
+
+impl<T, const N: usize> FromDart for [T; N]
+where
+    T: FromDartExceptPrimitive,
+{
+    fn from_dart(obj: &DartCObject) -> Result<Self, FromDartError> {
+        fixed(list(obj)?)
+    }
+}
+
+impl<T, const N: usize> FromDartExceptPrimitive for [T; N] where
+    T: FromDartExceptPrimitive
+{
+}
+
+impl<T> FromDart for HashSet<T>
+where
+    T: FromDartExceptPrimitive + Eq + Hash,
+{
+    fn from_dart(obj: &DartCObject) -> Result<Self, FromDartError> {
+        array(obj)?.map(T::from_dart).collect()
+    }
+}
+
+impl<T> FromDartExceptPrimitive for HashSet<T> where
+    T: FromDartExceptPrimitive + Eq + Hash
+{
+}
+
+/// Decoded from a `List` of `[key, value]` pairs.
+impl<K, V> FromDart for HashMap<K, V>
+where
+    K: FromDart + Eq + Hash,
+    V: FromDart,
+{
+    fn from_dart(obj: &DartCObject) -> Result<Self, FromDartError> {
+        array(obj)?.map(<(K, V)>::from_dart).collect()
+    }
+}
+
+impl<K, V> FromDartExceptPrimitive for HashMap<K, V>
+where
+    K: FromDart + Eq + Hash,
+    V: FromDart,
+{
+}
+
+impl<T> FromDart for Option<T>
+where
+    T: FromDart,
+{
+    fn from_dart(obj: &DartCObject) -> Result<Self, FromDartError> {
+        match obj.ty {
+            DartCObjectType::DartNull => Ok(None),
+            _ => T::from_dart(obj).map(Some),
+        }
+    }
+}
+
+impl<T> FromDartExceptPrimitive for Option<T> where T: FromDart {}
+
+macro_rules! impl_from_dart_for_tuple {
+    ($( ($len:literal => $($A:ident)+) )*) => {$(
+        impl<$($A: FromDart),+> FromDart for ($($A),+,) {
+            fn from_dart(obj: &DartCObject) -> Result<Self, FromDartError> {
+                let mut items = array(obj)?;
+                if items.len() != $len {
+                    return Err(FromDartError::LengthMismatch {
+                        expected: $len,
+                        found: items.len(),
+                    });
+                }
+                Ok(($(
+                    // the length was checked above
+                    $A::from_dart(items.next().unwrap())?
+                ),+,))
+            }
+        }
+        impl<$($A: FromDart),+> FromDartExceptPrimitive for ($($A),+,) {}
+    )*};
+}
+
+impl_from_dart_for_tuple! {
+    (1 => A)
+    (2 => A B)
+    (3 => A B C)
+    (4 => A B C D)
+    (5 => A B C D E)
+    (6 => A B C D E F)
+    (7 => A B C D E F G)
+    (8 => A B C D E F G H)
+    (9 => A B C D E F G H I)
+    (10 => A B C D E F G H I J)
+}
@@ -0,0 +1,144 @@
+use std::sync::atomic::Ordering;
+
+use crate::{
+    ffi::{DartCObject, DartCObjectType},
+    DartValue, FromDart, IntoDart, Isolate, POST_COBJECT,
+};
+
+/// A set of [`Isolate`]s that receive the same messages.
+///
+/// An isolate whose port is closed is removed from the group the first time
+/// a message can not be posted to it.
+///
+/// #### Example
+/// ```rust,ignore
+/// # use allo_isolate::{Isolate, IsolateGroup};
+/// let mut listeners = IsolateGroup::new();
+/// listeners.insert(Isolate::new(ui_port));
+/// listeners.insert(Isolate::new(sync_port));
+/// let received = listeners.broadcast(event);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct IsolateGroup {
+    isolates: Vec<Isolate>,
+}
+
+impl IsolateGroup {
+    /// Creates an empty group.
+    pub const fn new() -> Self {
+        Self {
+            isolates: Vec::new(),
+        }
+    }
+
+    /// Adds `isolate` to the group, returns `false` if it was already in it.
+    pub fn insert(&mut self, isolate: Isolate) -> bool {
+        if self.contains(isolate) {
+            return false;
+        }
+        self.isolates.push(isolate);
+        true
+    }
+
+    /// Removes `isolate` from the group, returns `false` if it was not in it.
+    pub fn remove(&mut self, isolate: Isolate) -> bool {
+        let len = self.isolates.len();
+        self.isolates.retain(|i| *i != isolate);
+        self.isolates.len() != len
+    }
+
+    /// Whether `isolate` is in the group.
+    pub fn contains(&self, isolate: Isolate) -> bool {
+        self.isolates.contains(&isolate)
+    }
+
+    /// The number of isolates in the group.
+    pub const fn len(&self) -> usize {
+        self.isolates.len()
+    }
+
+    /// Whether the group is empty.
+    pub const fn is_empty(&self) -> bool {
+        self.isolates.is_empty()
+    }
+
+    /// The isolates of the group, in the order they were added.
+    pub fn iter(&self) -> impl Iterator<Item = Isolate> + '_ {
+        self.isolates.iter().copied()
+    }
+
+    /// Posts `msg` to every isolate of the group, and returns how many of
+    /// them received it.
+    ///
+    /// The message is converted once and copied by the Dart VM for every
+    /// isolate. If it hands something over to Dart, like a
+    /// [`ZeroCopyBuffer`](crate::ZeroCopyBuffer), only the last isolate of
+    /// the group gets it, every other isolate gets its own [`DartValue`] copy
+    /// of the message. A message that can not be decoded into a
+    /// [`DartValue`] is only posted to the last isolate, the others stay in
+    /// the group but are not counted as having received it.
+    pub fn broadcast<T: IntoDart>(&mut self, msg: T) -> usize {
+        let Some(post_cobject) = POST_COBJECT.load(Ordering::Relaxed) else {
+            return 0;
+        };
+        if self.isolates.is_empty() {
+            return 0;
+        }
+        let mut obj = msg.into_dart();
+        if copied_by_vm(&obj) {
+            self.isolates.retain(|isolate| unsafe {
+                post_cobject(isolate.port, &mut obj)
+            });
+            return self.isolates.len();
+        }
+        let copy = DartValue::from_dart(&obj).ok();
+        let mut handed_over = Some(obj);
+        let mut left = self.isolates.len();
+        let mut received = 0;
+        self.isolates.retain(|isolate| {
+            left -= 1;
+            // a copy hands its own buffers over, so it is made again for
+            // every isolate.
+            let msg = match left {
+                0 => handed_over.take(),
+                _ => copy.clone().map(IntoDart::into_dart),
+            };
+            let Some(msg) = msg else {
+                return true;
+            };
+            let posted = isolate.post(msg);
+            received += posted as usize;
+            posted
+        });
+        received
+    }
+}
+
+/// Whether the VM only copies `obj`, and does not take ownership of anything
+/// in it, so it can be posted again.
+fn copied_by_vm(obj: &DartCObject) -> bool {
+    match obj.ty {
+        DartCObjectType::DartExternalTypedData
+        | DartCObjectType::DartUnmodifiableExternalTypedData
+        | DartCObjectType::DartNativePointer => false,
+        DartCObjectType::DartArray => crate::from_dart::array(obj)
+            .is_ok_and(|mut items| items.all(copied_by_vm)),
+        _ => true,
+    }
+}
+
+impl Extend<Isolate> for IsolateGroup {
+    fn extend<I: IntoIterator<Item = Isolate>>(&mut self, iter: I) {
+        for isolate in iter {
+            self.insert(isolate);
+        }
+    }
+}
+
+impl FromIterator<Isolate> for IsolateGroup {
+    fn from_iter<I: IntoIterator<Item = Isolate>>(iter: I) -> Self {
+        let mut group = Self::new();
+        group.extend(iter);
+        group
+    }
+}
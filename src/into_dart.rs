@@ -7,6 +7,7 @@ use std::{
 use crate::{
     dart_array::DartArray,
     ffi::{DartHandleFinalizer, *},
+    typed_data::{Float32x4, Float64x2, Int32x4},
 };
 
 /// A trait to convert between Rust types and Dart Types that could then
@@ -158,15 +159,50 @@ pub trait DartTypedDataTypeTrait {
     fn function_pointer_of_free_zero_copy_buffer() -> DartHandleFinalizer;
 }
 
+/// Copies `vec` into typed data of type `ty`, which has to hold `T`s.
+#[cfg_attr(feature = "zero-copy", allow(dead_code))]
+pub(crate) fn vec_to_dart_native_typed_data<T>(
+    vec: Vec<T>,
+    ty: DartTypedDataType,
+) -> DartCObject {
+    let mut vec = ManuallyDrop::new(vec);
+    let data = DartNativeTypedData {
+        ty,
+        length: vec.len() as isize,
+        values: vec.as_mut_ptr() as *mut _,
+    };
+    DartCObject {
+        ty: DartCObjectType::DartTypedData,
+        value: DartCObjectValue {
+            as_typed_data: data,
+        },
+    }
+}
+
 fn vec_to_dart_native_external_typed_data<T>(
     vec_from_rust: Vec<T>,
 ) -> DartCObject
+where
+    T: DartTypedDataTypeTrait,
+{
+    vec_to_dart_native_external_typed_data_of(
+        vec_from_rust,
+        T::dart_typed_data_type(),
+    )
+}
+
+/// Hands `vec` over as external typed data of type `ty`, which has to hold
+/// `T`s.
+pub(crate) fn vec_to_dart_native_external_typed_data_of<T>(
+    vec_from_rust: Vec<T>,
+    ty: DartTypedDataType,
+) -> DartCObject
 where
     T: DartTypedDataTypeTrait,
 {
     if vec_from_rust.is_empty() {
         let data = DartNativeTypedData {
-            ty: T::dart_typed_data_type(),
+            ty,
             length: 0,
             values: std::ptr::null_mut(),
         };
@@ -188,7 +224,7 @@ where
         ty: DartCObjectType::DartExternalTypedData,
         value: DartCObjectValue {
             as_external_typed_data: DartNativeExternalTypedData {
-                ty: T::dart_typed_data_type(),
+                ty,
                 length: length as isize,
                 data: ptr as *mut u8,
                 peer: Box::into_raw(Box::new(vec)).cast(),
@@ -221,18 +257,7 @@ macro_rules! dart_typed_data_type_trait_impl {
             #[cfg(not(feature="zero-copy"))]
             impl IntoDart for Vec<$rust_type> {
                 fn into_dart(self) -> DartCObject {
-                    let mut vec = ManuallyDrop::new(self);
-                    let data = DartNativeTypedData {
-                        ty: $rust_type::dart_typed_data_type(),
-                        length: vec.len() as isize,
-                        values: vec.as_mut_ptr() as *mut _,
-                    };
-                    DartCObject {
-                        ty: DartCObjectType::DartTypedData,
-                        value: DartCObjectValue {
-                            as_typed_data: data,
-                        },
-                    }
+                    vec_to_dart_native_typed_data(self, $rust_type::dart_typed_data_type())
                 }
             }
             #[cfg(feature="zero-copy")]
@@ -265,6 +290,8 @@ macro_rules! dart_typed_data_type_trait_impl {
                 $(
                     $dart_type => visitor.visit::<$rust_type>(),
                 )+
+                // they hold bytes, like a `Uint8List`
+                DartTypedDataType::Uint8Clamped | DartTypedDataType::ByteData => visitor.visit::<u8>(),
                 _ => panic!("visit_dart_typed_data_type see unexpected DartTypedDataType={:?}", ty)
             }
         }
@@ -281,7 +308,10 @@ dart_typed_data_type_trait_impl!(
     DartTypedDataType::Int64 => i64 + free_zero_copy_buffer_i64,
     DartTypedDataType::Uint64 => u64 + free_zero_copy_buffer_u64,
     DartTypedDataType::Float32 => f32 + free_zero_copy_buffer_f32,
-    DartTypedDataType::Float64 => f64 + free_zero_copy_buffer_f64
+    DartTypedDataType::Float64 => f64 + free_zero_copy_buffer_f64,
+    DartTypedDataType::Int32x4 => Int32x4 + free_zero_copy_buffer_int32x4,
+    DartTypedDataType::Float32x4 => Float32x4 + free_zero_copy_buffer_float32x4,
+    DartTypedDataType::Float64x2 => Float64x2 + free_zero_copy_buffer_float64x2
 );
 
 macro_rules! isize_usize {
@@ -19,7 +19,8 @@
 //! Interacting with Dart VM directly isn't that easy, that is why we created
 //! that library, it provides [`IntoDart`] trait to convert between Rust data
 //! types and Dart Types, and by default it is implemented for all common rust
-//! types.
+//! types. The [`FromDart`] trait does the opposite, for messages that Dart
+//! sends to Rust.
 //!
 //! ### Example
 //!
@@ -31,17 +32,64 @@
 //!   For example, `Vec<u8>` in Rust will be moved to the Dart side
 //!   as `UInt8List` without any copy operation,
 //!   which can have performance benefits.
+//! - `futures`: Post every item of a `Stream`, see
+//!   [`Isolate::forward_stream`], or use an [`Isolate`] as a `Sink`, see
+//!   [`IsolateSink`].
+//! - `serde`: Send any `Serialize` type, see [`to_dart`] and [`Serde`], and
+//!   read received messages into any `Deserialize` type with [`from_dart`].
+//! - `derive`: `#[derive(IntoDart)]` for structs and enums, see
+//!   [`IntoDart`](macro@IntoDart).
+//! - `bytes`: Send `bytes::Bytes` without copying, with [`ZeroCopyBuffer`].
+//! - `memmap2`: Send a memory mapped file without reading it, with
+//!   [`ZeroCopyBuffer`], see also [`ExternalBuffer`].
+//! - `rpc`: Handle requests from Dart and reply to them, see [`rpc`].
+//! - `testing`: A mock of the Dart VM for unit tests, see [`testing`].
 
 /// Holds the Raw Dart FFI Types Required to send messages to Isolate
 use atomic::Atomic;
-use std::{future::Future, sync::atomic::Ordering};
+use std::{
+    collections::BTreeMap,
+    ffi::CString,
+    fmt,
+    future::Future,
+    mem::ManuallyDrop,
+    panic::{self, AssertUnwindSafe},
+    sync::{atomic::Ordering, Arc, Mutex, PoisonError},
+};
 
+pub use api_dl::{
+    dart_api_dl, initialize_api_dl, store_dart_api_dl, ApiDlError, DartApiDl,
+};
+pub use batch::BatchingIsolate;
+pub use external::{ExternalBuffer, ExternalTypedData};
 pub use ffi::ZeroCopyBuffer;
+pub use from_dart::{FromDart, FromDartError, FromDartExceptPrimitive};
+pub use group::IsolateGroup;
 pub use into_dart::{IntoDart, IntoDartExceptPrimitive};
+pub use message_builder::{MessageBuilder, MessageNode};
+pub use native_handle::NativeHandle;
+pub use pool::{BufferPool, PoolStats, PooledBuffer};
+pub use send_port::{Capability, SendPort};
+pub use typed_data::{ByteData, Float32x4, Float64x2, Int32x4, Uint8Clamped};
+pub use value::{DartValue, TypedData};
 
+#[cfg(feature = "derive")]
+pub use allo_isolate_derive::IntoDart;
+
+mod api_dl;
+mod batch;
 mod dart_array;
+mod external;
+mod from_dart;
+mod group;
 mod into_dart;
 mod into_dart_extra;
+mod message_builder;
+mod native_handle;
+mod pool;
+mod send_port;
+mod typed_data;
+mod value;
 
 #[cfg(feature = "catch-unwind")]
 mod catch_unwind;
@@ -52,12 +100,42 @@ mod chrono;
 #[cfg(feature = "uuid")]
 mod uuid;
 
+#[cfg(feature = "testing")]
+pub mod testing;
+
+#[cfg(feature = "serde")]
+mod serde;
+#[cfg(feature = "serde")]
+pub use crate::serde::{
+    from_dart, to_dart, DeserializeError, Serde, SerializeError,
+};
+
+#[cfg(feature = "rpc")]
+pub mod rpc;
+
+#[cfg(feature = "futures")]
+mod sink;
+#[cfg(feature = "futures")]
+pub use sink::IsolateSink;
+
 pub mod ffi;
 
 // Please don't use `AtomicPtr` here
 // see https://github.com/rust-lang/rfcs/issues/2481
 static POST_COBJECT: Atomic<Option<ffi::DartPostCObjectFnType>> =
     Atomic::new(None);
+static POST_INTEGER: Atomic<Option<ffi::DartPostIntegerFnType>> =
+    Atomic::new(None);
+static NEW_NATIVE_PORT: Atomic<Option<ffi::DartNewNativePortFnType>> =
+    Atomic::new(None);
+static CLOSE_NATIVE_PORT: Atomic<Option<ffi::DartCloseNativePortFnType>> =
+    Atomic::new(None);
+
+/// The Rust handlers of every open [`NativePort`], by port id.
+static NATIVE_PORT_HANDLERS: Mutex<BTreeMap<ffi::DartPort, NativeHandler>> =
+    Mutex::new(BTreeMap::new());
+
+type NativeHandler = Arc<dyn Fn(&ffi::DartCObject) + Send + Sync>;
 
 /// Stores the function pointer of `Dart_PostCObject`, this only should be
 /// called once at the start up of the Dart/Flutter Application. it is exported
@@ -94,9 +172,74 @@ pub unsafe extern "C" fn store_dart_post_cobject(
     POST_COBJECT.store(Some(ptr), Ordering::Relaxed);
 }
 
+/// Stores the function pointer of `Dart_PostInteger`, used by
+/// [`Isolate::post_integer`], this only should be called once at the start up
+/// of the Dart/Flutter Application. it is exported and marked as
+/// `#[no_mangle]`.
+///
+/// #### Safety
+/// This function should only be called once at the start up of the Dart
+/// application.
+///
+/// ### Example
+/// ```dart,ignore
+/// // assumes that _dl is the `DynamicLibrary`
+/// final storeDartPostInteger = _dl.lookupFunction<
+///     Void Function(Pointer<Void>),
+///     void Function(Pointer<Void>)>('store_dart_post_integer');
+///
+/// // where `postInteger` is the address of `Dart_PostInteger_DL`
+/// storeDartPostInteger(postInteger);
+/// ```
+#[no_mangle]
+pub unsafe extern "C" fn store_dart_post_integer(
+    ptr: ffi::DartPostIntegerFnType,
+) {
+    POST_INTEGER.store(Some(ptr), Ordering::Relaxed);
+}
+
+/// Stores the function pointer of `Dart_NewNativePort`, this only should be
+/// called once at the start up of the Dart/Flutter Application, together with
+/// [`store_dart_close_native_port`]. it is exported and marked as
+/// `#[no_mangle]`.
+///
+/// #### Safety
+/// This function should only be called once at the start up of the Dart
+/// application.
+///
+/// ### Example
+/// ```dart,ignore
+/// // assumes that _dl is the `DynamicLibrary`
+/// final storeDartNewNativePort = _dl.lookupFunction<
+///     Void Function(Pointer<Void>),
+///     void Function(Pointer<Void>)>('store_dart_new_native_port');
+///
+/// // where `newNativePort` is the address of `Dart_NewNativePort_DL`
+/// storeDartNewNativePort(newNativePort);
+/// ```
+#[no_mangle]
+pub unsafe extern "C" fn store_dart_new_native_port(
+    ptr: ffi::DartNewNativePortFnType,
+) {
+    NEW_NATIVE_PORT.store(Some(ptr), Ordering::Relaxed);
+}
+
+/// Stores the function pointer of `Dart_CloseNativePort`, see
+/// [`store_dart_new_native_port`].
+///
+/// #### Safety
+/// This function should only be called once at the start up of the Dart
+/// application.
+#[no_mangle]
+pub unsafe extern "C" fn store_dart_close_native_port(
+    ptr: ffi::DartCloseNativePortFnType,
+) {
+    CLOSE_NATIVE_PORT.store(Some(ptr), Ordering::Relaxed);
+}
+
 /// Simple wrapper around the Dart Isolate Port, nothing
 /// else.
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
 pub struct Isolate {
     port: i64,
 }
@@ -117,7 +260,8 @@ impl Isolate {
     /// Post an object to the [`Isolate`] over the port
     /// Object must implement [`IntoDart`].
     ///
-    /// returns `true` if the message posted successfully, otherwise `false`
+    /// returns `true` if the message posted successfully, otherwise `false`,
+    /// see [`Isolate::try_post`] if you need to know why it failed.
     ///
     /// #### Safety
     /// This assumes that you called [`store_dart_post_cobject`] and we have
@@ -131,22 +275,95 @@ impl Isolate {
     /// isolate.post("Hello Dart !");
     /// ```
     pub fn post(&self, msg: impl IntoDart) -> bool {
-        if let Some(func) = POST_COBJECT.load(Ordering::Relaxed) {
-            unsafe {
-                let mut msg = msg.into_dart();
-                // Send the message
-                let result = func(self.port, &mut msg);
-                if !result {
-                    ffi::run_destructors(&msg);
-                }
-                // I like that dance haha
-                result
+        self.try_post(msg).is_ok()
+    }
+
+    /// Post an object to the [`Isolate`] over the port
+    /// Object must implement [`IntoDart`].
+    ///
+    /// Same as [`Isolate::post`] but returns a [`PostError`] describing why
+    /// the message could not be posted.
+    ///
+    /// #### Example
+    /// ```rust
+    /// # use allo_isolate::{Isolate, PostError};
+    /// let isolate = Isolate::new(42);
+    /// // `store_dart_post_cobject` was never called here.
+    /// assert_eq!(isolate.try_post("Hello Dart !"), Err(PostError::NotInitialized));
+    /// ```
+    pub fn try_post(&self, msg: impl IntoDart) -> Result<(), PostError> {
+        let func = POST_COBJECT
+            .load(Ordering::Relaxed)
+            .ok_or(PostError::NotInitialized)?;
+        unsafe {
+            let mut msg = msg.into_dart();
+            // Send the message
+            if func(self.port, &mut msg) {
+                Ok(())
+            } else {
+                // the VM did not take ownership of anything, so we have to
+                // release what was meant to be handed over.
+                ffi::run_destructors(&msg);
+                Err(PostError::PortClosed)
             }
-        } else {
-            false
         }
     }
 
+    /// Post an integer to the [`Isolate`] over the port, with
+    /// `Dart_PostInteger` which is cheaper than [`Isolate::post`], handy for
+    /// progress counters and the like.
+    ///
+    /// Falls back to [`Isolate::post`] if [`store_dart_post_integer`] (or
+    /// [`store_dart_api_dl`]) was never called.
+    ///
+    /// returns `true` if the message posted successfully, otherwise `false`
+    ///
+    /// #### Example
+    /// ```rust
+    /// # use allo_isolate::Isolate;
+    /// let isolate = Isolate::new(42);
+    /// isolate.post_integer(100);
+    /// ```
+    pub fn post_integer(&self, value: i64) -> bool {
+        match POST_INTEGER.load(Ordering::Relaxed) {
+            Some(func) => unsafe { func(self.port, value) },
+            None => self.post(value),
+        }
+    }
+
+    /// Post a message built with a [`MessageBuilder`] to the [`Isolate`].
+    ///
+    /// Every object, string and array of the message is allocated in the
+    /// builder, and they are all freed at once after the message is posted,
+    /// which is a lot cheaper than [`Isolate::post`] for large nested
+    /// messages. `build` is not called if the message can not be posted.
+    ///
+    /// returns `true` if the message posted successfully, otherwise `false`
+    ///
+    /// #### Example
+    /// ```rust
+    /// # use allo_isolate::Isolate;
+    /// let isolate = Isolate::new(42);
+    /// let rows = vec![("a", 1), ("b", 2)];
+    /// isolate.post_with(|b| {
+    ///     b.list(
+    ///         rows.iter()
+    ///             .map(|(name, count)| b.list([b.string(name), b.int(*count)])),
+    ///     )
+    /// });
+    /// ```
+    pub fn post_with<F>(&self, build: F) -> bool
+    where
+        F: for<'a> FnOnce(&'a MessageBuilder) -> MessageNode<'a>,
+    {
+        let Some(func) = POST_COBJECT.load(Ordering::Relaxed) else {
+            return false;
+        };
+        let builder = MessageBuilder::new();
+        let root = build(&builder);
+        unsafe { builder.post(func, self.port, root) }
+    }
+
     /// Consumes `Self`, Runs the task, await for the result and then post it
     /// to the [`Isolate`] over the port
     /// Result must implement [`IntoDart`].
@@ -188,4 +405,269 @@ impl Isolate {
             .await
             .map(|msg| Ok(self.post(msg)))?
     }
+
+    /// Consumes `Self`, posts every item of the stream to the [`Isolate`]
+    /// over the port, as they come.
+    ///
+    /// Stops as soon as an item could not be posted, and returns why, so a
+    /// closed port does not keep the stream running for nothing.
+    ///
+    /// #### Example
+    /// ```rust,ignore
+    /// # use allo_isolate::Isolate;
+    /// use async_std::task;
+    /// let isolate = Isolate::new(42);
+    /// let progress = futures::stream::iter(0..=100);
+    /// task::spawn(isolate.forward_stream(progress));
+    /// ```
+    #[cfg(feature = "futures")]
+    pub async fn forward_stream<S>(self, stream: S) -> Result<(), PostError>
+    where
+        S: futures::Stream,
+        S::Item: IntoDart,
+    {
+        use futures::StreamExt;
+
+        futures::pin_mut!(stream);
+        while let Some(item) = stream.next().await {
+            self.try_post(item)?;
+        }
+        Ok(())
+    }
+
+    /// Same as [`Isolate::forward_stream`], but posts `end` once the stream
+    /// is exhausted, so Dart knows that nothing else is coming.
+    #[cfg(feature = "futures")]
+    pub async fn forward_stream_with_end<S, E>(
+        self,
+        stream: S,
+        end: E,
+    ) -> Result<(), PostError>
+    where
+        S: futures::Stream,
+        S::Item: IntoDart,
+        E: IntoDart,
+    {
+        self.forward_stream(stream).await?;
+        self.try_post(end)
+    }
+
+    /// Creates a [`BatchingIsolate`] that posts messages to this [`Isolate`]
+    /// in batches.
+    pub const fn batching(self) -> BatchingIsolate {
+        BatchingIsolate::new(self)
+    }
+
+    /// Creates an [`IsolateSink`] that posts every item to this [`Isolate`].
+    #[cfg(feature = "futures")]
+    pub const fn sink<T>(self) -> IsolateSink<T> {
+        IsolateSink::new(self)
+    }
+}
+
+/// A port owned by Rust, Dart can post messages to it and they will be
+/// handed to a Rust closure.
+///
+/// The port is closed when the [`NativePort`] is dropped, so keep it around
+/// for as long as you want to receive messages.
+///
+/// #### Example
+/// ```rust,ignore
+/// # use allo_isolate::{FromDart, NativePort};
+/// let port = NativePort::new("counter", |msg| {
+///     if let Ok(n) = i64::from_dart(msg) {
+///         println!("got {}", n);
+///     }
+/// })?;
+/// // send `port.port()` to Dart, which posts to it with
+/// // `SendPort.fromNativePort(port)`.
+/// ```
+#[derive(Debug)]
+pub struct NativePort {
+    port: ffi::DartPort,
 }
+
+impl NativePort {
+    /// Opens a new native port named `name` (only used in debugging
+    /// messages), every message posted to it is passed to `handler`.
+    /// `name` must not contain a nul character.
+    ///
+    /// Messages are handled one at a time, the [`ffi::DartCObject`] is owned
+    /// by the Dart VM and only lives while `handler` runs, see
+    /// [`FromDart`] to decode it.
+    ///
+    /// #### Safety
+    /// This assumes that you called [`store_dart_new_native_port`] and
+    /// [`store_dart_close_native_port`], we do check if they are not null.
+    pub fn new<F>(name: &str, handler: F) -> Result<Self, NativePortError>
+    where
+        F: Fn(&ffi::DartCObject) + Send + Sync + 'static,
+    {
+        Self::open(name, false, Arc::new(handler))
+    }
+
+    /// Same as [`NativePort::new`], but the Dart VM is allowed to call
+    /// `handler` for several messages at the same time.
+    pub fn new_concurrent<F>(
+        name: &str,
+        handler: F,
+    ) -> Result<Self, NativePortError>
+    where
+        F: Fn(&ffi::DartCObject) + Send + Sync + 'static,
+    {
+        Self::open(name, true, Arc::new(handler))
+    }
+
+    fn open(
+        name: &str,
+        handle_concurrently: bool,
+        handler: NativeHandler,
+    ) -> Result<Self, NativePortError> {
+        let new_native_port = NEW_NATIVE_PORT
+            .load(Ordering::Relaxed)
+            .ok_or(NativePortError::NotInitialized)?;
+        if CLOSE_NATIVE_PORT.load(Ordering::Relaxed).is_none() {
+            // we would not be able to close it again.
+            return Err(NativePortError::NotInitialized);
+        }
+        let name =
+            CString::new(name).map_err(|_| NativePortError::InvalidName)?;
+        // hold the lock until the handler is registered, so a message that
+        // arrives right away waits for it instead of being dropped.
+        let mut handlers = NATIVE_PORT_HANDLERS
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        let port = unsafe {
+            new_native_port(
+                name.as_ptr(),
+                dispatch_native_message,
+                handle_concurrently,
+            )
+        };
+        if port == ffi::ILLEGAL_PORT {
+            return Err(NativePortError::Rejected);
+        }
+        handlers.insert(port, handler);
+        Ok(Self { port })
+    }
+
+    /// The id of the port, this is what Dart needs to post to it.
+    pub const fn port(&self) -> ffi::DartPort {
+        self.port
+    }
+
+    /// An [`Isolate`] that posts to this port, mostly useful to post
+    /// messages to yourself.
+    pub const fn isolate(&self) -> Isolate {
+        Isolate::new(self.port)
+    }
+
+    /// A [`SendPort`] to this port, that can be sent to Dart so that it
+    /// replies here.
+    pub const fn send_port(&self) -> SendPort {
+        SendPort::new(self.port)
+    }
+
+    /// Closes the port, no more messages will be handed to the handler.
+    ///
+    /// returns `true` if the Dart VM closed the port, dropping the
+    /// [`NativePort`] does the same but ignores the result.
+    pub fn close(self) -> bool {
+        ManuallyDrop::new(self).close_port()
+    }
+
+    fn close_port(&self) -> bool {
+        // `open` made sure that it is there.
+        let closed = CLOSE_NATIVE_PORT
+            .load(Ordering::Relaxed)
+            .map(|close_native_port| unsafe { close_native_port(self.port) })
+            .unwrap_or(false);
+        NATIVE_PORT_HANDLERS
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .remove(&self.port);
+        closed
+    }
+}
+
+impl Drop for NativePort {
+    fn drop(&mut self) {
+        self.close_port();
+    }
+}
+
+/// The [`ffi::DartNativeMessageHandler`] of every [`NativePort`], it looks
+/// up the Rust handler of the port and calls it.
+unsafe extern "C" fn dispatch_native_message(
+    port: ffi::DartPort,
+    message: *mut ffi::DartCObject,
+) {
+    let handler = NATIVE_PORT_HANDLERS
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .get(&port)
+        .cloned();
+    if let (Some(handler), Some(message)) = (handler, message.as_ref()) {
+        // we must not unwind into the Dart VM.
+        let _ = panic::catch_unwind(AssertUnwindSafe(|| handler(message)));
+    }
+}
+
+/// The reason why a message could not be posted to an [`Isolate`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum PostError {
+    /// [`store_dart_post_cobject`] (or [`store_dart_api_dl`]) was never
+    /// called, so there is no `Dart_PostCObject` to post with.
+    NotInitialized,
+    /// `Dart_PostCObject` refused the message, which means that the port is
+    /// closed (or was never open), posting to it again will not help.
+    PortClosed,
+}
+
+impl fmt::Display for PostError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotInitialized => f.write_str(
+                "Dart_PostCObject is not available, \
+                 did you call store_dart_post_cobject or store_dart_api_dl?",
+            ),
+            Self::PortClosed => f.write_str("the port is closed"),
+        }
+    }
+}
+
+impl std::error::Error for PostError {}
+
+/// The reason why a [`NativePort`] could not be opened.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum NativePortError {
+    /// [`store_dart_new_native_port`] or [`store_dart_close_native_port`]
+    /// was never called, nor [`store_dart_api_dl`].
+    NotInitialized,
+    /// `Dart_NewNativePort` returned [`ffi::ILLEGAL_PORT`].
+    Rejected,
+    /// The name of the port contains a nul character.
+    InvalidName,
+}
+
+impl fmt::Display for NativePortError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotInitialized => f.write_str(
+                "Dart_NewNativePort or Dart_CloseNativePort is not available, \
+                 did you call store_dart_new_native_port and \
+                 store_dart_close_native_port, or store_dart_api_dl?",
+            ),
+            Self::Rejected => {
+                f.write_str("Dart_NewNativePort could not open a port")
+            },
+            Self::InvalidName => {
+                f.write_str("the name of the port contains a nul character")
+            },
+        }
+    }
+}
+
+impl std::error::Error for NativePortError {}
@@ -0,0 +1,308 @@
+//! An arena for the objects of one message, see [`Isolate::post_with`].
+
+use std::{
+    cell::RefCell, collections::HashSet, ffi::c_char, fmt, iter,
+    marker::PhantomData, mem::ManuallyDrop,
+};
+
+use crate::{ffi::*, from_dart, IntoDart};
+
+#[cfg(doc)]
+use crate::Isolate;
+
+/// Builds a message whose objects, strings and arrays all live in a few
+/// large chunks, freed at once after the message is posted.
+///
+/// [`IntoDart`] allocates every string and every array of a message on its
+/// own, and frees them one by one after the message is posted, which adds up
+/// for large nested messages. The builder is handed out by
+/// [`Isolate::post_with`], the message is made of the [`MessageNode`]s it
+/// returns.
+///
+/// #### Example
+/// ```rust,ignore
+/// # use allo_isolate::Isolate;
+/// isolate.post_with(|b| {
+///     b.list(rows.iter().map(|row| {
+///         b.list([b.string(&row.name), b.int(row.count), b.value(row.samples)])
+///     }))
+/// });
+/// ```
+pub struct MessageBuilder {
+    objects: RefCell<Chunks<ManuallyDrop<DartCObject>>>,
+    pointers: RefCell<Chunks<*mut DartCObject>>,
+    strings: RefCell<Chunks<u8>>,
+    /// Objects made by [`IntoDart`], they free what they hold themselves.
+    /// What they hand over to Dart is released when they are left out of the
+    /// message, or when it is not posted.
+    owned: RefCell<Vec<DartCObject>>,
+    /// The items of the lists that are being built, nested lists push theirs
+    /// on top.
+    items: RefCell<Vec<(DartCObjectType, DartCObjectValue)>>,
+}
+
+/// A value of a message built with a [`MessageBuilder`], it can only be used
+/// while the builder is alive.
+///
+/// A node is part of the message only once, so that what [`IntoDart`] hands
+/// over to Dart is not handed over twice.
+#[allow(missing_copy_implementations)]
+pub struct MessageNode<'a> {
+    ty: DartCObjectType,
+    value: DartCObjectValue,
+    _builder: PhantomData<&'a MessageBuilder>,
+}
+
+impl MessageBuilder {
+    pub(crate) const fn new() -> Self {
+        Self {
+            objects: RefCell::new(Chunks::new(256)),
+            pointers: RefCell::new(Chunks::new(256)),
+            strings: RefCell::new(Chunks::new(4096)),
+            owned: RefCell::new(Vec::new()),
+            items: RefCell::new(Vec::new()),
+        }
+    }
+
+    /// `null`
+    pub const fn null(&self) -> MessageNode<'_> {
+        MessageNode::new(
+            DartCObjectType::DartNull,
+            DartCObjectValue { as_bool: false },
+        )
+    }
+
+    /// A `bool`.
+    pub const fn bool(&self, value: bool) -> MessageNode<'_> {
+        MessageNode::new(
+            DartCObjectType::DartBool,
+            DartCObjectValue { as_bool: value },
+        )
+    }
+
+    /// An `int`.
+    pub const fn int(&self, value: i64) -> MessageNode<'_> {
+        MessageNode::new(
+            DartCObjectType::DartInt64,
+            DartCObjectValue { as_int64: value },
+        )
+    }
+
+    /// A `double`.
+    pub const fn double(&self, value: f64) -> MessageNode<'_> {
+        MessageNode::new(
+            DartCObjectType::DartDouble,
+            DartCObjectValue { as_double: value },
+        )
+    }
+
+    /// A `String`, copied into the builder.
+    ///
+    /// Like for a `String` sent with [`IntoDart`], Dart gets an empty string
+    /// if `value` contains a nul character.
+    pub fn string(&self, value: &str) -> MessageNode<'_> {
+        let value = if value.contains('\0') { "" } else { value };
+        let bytes = value.bytes().chain(iter::once(0));
+        let string = self.strings.borrow_mut().alloc(value.len() + 1, bytes);
+        MessageNode::new(
+            DartCObjectType::DartString,
+            DartCObjectValue {
+                as_string: string.cast::<c_char>(),
+            },
+        )
+    }
+
+    /// A `List` of `items`, which can be built with this builder too.
+    pub fn list<'a, I>(&'a self, items: I) -> MessageNode<'a>
+    where
+        I: IntoIterator<Item = MessageNode<'a>>,
+    {
+        let start = self.items.borrow().len();
+        for item in items {
+            self.items.borrow_mut().push((item.ty, item.value));
+        }
+        let mut items = self.items.borrow_mut();
+        let length = items.len() - start;
+        let objects = self.objects.borrow_mut().alloc(
+            length,
+            items.drain(start..).map(|(ty, value)| {
+                ManuallyDrop::new(DartCObject { ty, value })
+            }),
+        );
+        // `ManuallyDrop` has the same layout as the object it holds.
+        let objects = objects.cast::<DartCObject>();
+        let values = self
+            .pointers
+            .borrow_mut()
+            .alloc(length, (0..length).map(|i| unsafe { objects.add(i) }));
+        MessageNode::new(
+            DartCObjectType::DartArray,
+            DartCObjectValue {
+                as_array: DartNativeArray {
+                    length: length as isize,
+                    values,
+                },
+            },
+        )
+    }
+
+    /// Any value, converted with [`IntoDart`].
+    ///
+    /// This is how typed data is added, a [`ZeroCopyBuffer`] is still handed
+    /// over to Dart without being copied. A value that is left out of the
+    /// message is released once the message is posted.
+    pub fn value<'a>(&'a self, value: impl IntoDart) -> MessageNode<'a> {
+        let obj = value.into_dart();
+        let node = MessageNode::new(obj.ty, obj.value);
+        self.owned.borrow_mut().push(obj);
+        node
+    }
+
+    /// Posts `root` with `post_cobject`, the message is freed along with the
+    /// builder.
+    pub(crate) unsafe fn post(
+        &self,
+        post_cobject: DartPostCObjectFnType,
+        port: DartPort,
+        root: MessageNode<'_>,
+    ) -> bool {
+        let mut msg = root.into_object();
+        let posted = post_cobject(port, &mut *msg);
+        let owned = self.owned.take();
+        if posted {
+            // the VM only took ownership of what is part of the message.
+            let mut reachable = HashSet::new();
+            if !owned.is_empty() {
+                collect_identities(&msg, &mut reachable);
+            }
+            owned
+                .iter()
+                .filter(|obj| {
+                    identity(obj).is_some_and(|id| !reachable.contains(&id))
+                })
+                .for_each(|obj| run_destructors(obj));
+        } else {
+            // the VM did not take ownership of anything, so what `IntoDart`
+            // meant to hand over is released here.
+            owned.iter().for_each(|obj| run_destructors(obj));
+        }
+        posted
+    }
+}
+
+impl Drop for MessageBuilder {
+    fn drop(&mut self) {
+        // the message was never posted, building it panicked.
+        for obj in self.owned.get_mut().iter() {
+            unsafe { run_destructors(obj) };
+        }
+    }
+}
+
+/// What tells `obj` apart from the other objects of a message, for the
+/// objects that can hold something handed over to Dart.
+const fn identity(obj: &DartCObject) -> Option<*const ()> {
+    let ptr = unsafe {
+        match obj.ty {
+            DartCObjectType::DartArray => {
+                obj.value.as_array.values as *const ()
+            },
+            DartCObjectType::DartExternalTypedData
+            | DartCObjectType::DartUnmodifiableExternalTypedData => {
+                obj.value.as_external_typed_data.peer as *const ()
+            },
+            DartCObjectType::DartNativePointer => {
+                obj.value.as_native_pointer.ptr as *const ()
+            },
+            _ => return None,
+        }
+    };
+    Some(ptr)
+}
+
+fn collect_identities(obj: &DartCObject, ids: &mut HashSet<*const ()>) {
+    if let Some(id) = identity(obj) {
+        ids.insert(id);
+    }
+    if let Ok(items) = from_dart::array(obj) {
+        items.for_each(|item| collect_identities(item, ids));
+    }
+}
+
+impl fmt::Debug for MessageBuilder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MessageBuilder")
+            .field("objects", &self.objects.borrow().len())
+            .field("strings", &self.strings.borrow().len())
+            .field("owned", &self.owned.borrow().len())
+            .finish()
+    }
+}
+
+impl<'a> MessageNode<'a> {
+    const fn new(ty: DartCObjectType, value: DartCObjectValue) -> Self {
+        Self {
+            ty,
+            value,
+            _builder: PhantomData,
+        }
+    }
+
+    /// The builder owns what the object points to, so it must not be
+    /// dropped.
+    const fn into_object(self) -> ManuallyDrop<DartCObject> {
+        ManuallyDrop::new(DartCObject {
+            ty: self.ty,
+            value: self.value,
+        })
+    }
+}
+
+impl fmt::Debug for MessageNode<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MessageNode")
+            .field("ty", &self.ty)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Items that never move once allocated, since a chunk is never grown past
+/// its capacity.
+struct Chunks<T> {
+    chunks: Vec<Vec<T>>,
+    chunk_len: usize,
+}
+
+impl<T> Chunks<T> {
+    const fn new(chunk_len: usize) -> Self {
+        Self {
+            chunks: Vec::new(),
+            chunk_len,
+        }
+    }
+
+    /// Moves the `len` first `items` next to each other, and returns where
+    /// they start.
+    fn alloc(
+        &mut self,
+        len: usize,
+        items: impl IntoIterator<Item = T>,
+    ) -> *mut T {
+        let fits = self
+            .chunks
+            .last()
+            .is_some_and(|chunk| chunk.capacity() - chunk.len() >= len);
+        if !fits {
+            self.chunks
+                .push(Vec::with_capacity(len.max(self.chunk_len)));
+        }
+        let chunk = self.chunks.last_mut().expect("there is a chunk with room");
+        let start = chunk.len();
+        chunk.extend(items.into_iter().take(len));
+        unsafe { chunk.as_mut_ptr().add(start) }
+    }
+
+    fn len(&self) -> usize {
+        self.chunks.iter().map(Vec::len).sum()
+    }
+}
@@ -0,0 +1,79 @@
+use std::{
+    ffi::c_void,
+    mem,
+    ops::{Deref, DerefMut},
+};
+
+use crate::{ffi::*, IntoDart, IntoDartExceptPrimitive};
+
+/// Hands an opaque Rust object to Dart, which owns it from then on.
+///
+/// It is sent as a native pointer, Dart receives the address of the object
+/// and Dart's GC knows about its size. The object is dropped once Dart
+/// collects the message, or right away if it could not be posted.
+///
+/// #### Example
+/// ```rust,ignore
+/// # use allo_isolate::{Isolate, NativeHandle};
+/// let isolate = Isolate::new(port);
+/// isolate.post(NativeHandle::new(Database::open("app.db")?));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct NativeHandle<T>(Box<T>);
+
+impl<T> NativeHandle<T> {
+    /// Moves `value` to the heap, to be sent to Dart.
+    pub fn new(value: T) -> Self {
+        Self(Box::new(value))
+    }
+
+    /// Returns the object, if it was not sent after all.
+    pub fn into_inner(self) -> T {
+        *self.0
+    }
+}
+
+impl<T> From<Box<T>> for NativeHandle<T> {
+    fn from(value: Box<T>) -> Self {
+        Self(value)
+    }
+}
+
+impl<T> Deref for NativeHandle<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T> DerefMut for NativeHandle<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.0
+    }
+}
+
+unsafe extern "C" fn drop_native_handle<T>(
+    _isolate_callback_data: *mut c_void,
+    peer: *mut c_void,
+) {
+    drop(Box::from_raw(peer.cast::<T>()));
+}
+
+// the finalizer runs on whatever thread Dart's GC is on.
+impl<T: Send + 'static> IntoDart for NativeHandle<T> {
+    fn into_dart(self) -> DartCObject {
+        DartCObject {
+            ty: DartCObjectType::DartNativePointer,
+            value: DartCObjectValue {
+                as_native_pointer: DartNativePointer {
+                    ptr: Box::into_raw(self.0) as isize,
+                    size: mem::size_of::<T>() as isize,
+                    callback: drop_native_handle::<T>,
+                },
+            },
+        }
+    }
+}
+
+impl<T: Send + 'static> IntoDartExceptPrimitive for NativeHandle<T> {}
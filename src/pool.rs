@@ -0,0 +1,183 @@
+//! Recycling of the buffers that are sent to Dart without copying them.
+
+use std::{
+    mem::{self, ManuallyDrop},
+    ops::{Deref, DerefMut},
+    ptr,
+    sync::{Arc, Mutex, MutexGuard, PoisonError},
+};
+
+use crate::{
+    external::owner_to_dart_native_external_typed_data, ffi::*,
+    into_dart::DartTypedDataTypeTrait, IntoDart, IntoDartExceptPrimitive,
+};
+
+/// A pool of buffers that go back to the pool, instead of being freed, once
+/// Dart finalizes them.
+///
+/// Posting a lot of large buffers, like the frames of a camera, would
+/// otherwise allocate and free one buffer per message. The pool is cheap to
+/// clone, all clones share the same buffers.
+///
+/// #### Example
+/// ```rust,ignore
+/// # use allo_isolate::{BufferPool, Isolate, ZeroCopyBuffer};
+/// let pool = BufferPool::new();
+/// for frame in camera.frames() {
+///     let mut buffer = pool.get();
+///     buffer.extend_from_slice(frame.pixels());
+///     isolate.post(ZeroCopyBuffer(buffer));
+/// }
+/// ```
+#[derive(Debug)]
+pub struct BufferPool<T> {
+    state: Arc<Mutex<State<T>>>,
+}
+
+#[derive(Debug)]
+struct State<T> {
+    idle: Vec<Vec<T>>,
+    stats: PoolStats,
+}
+
+/// Counters of a [`BufferPool`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct PoolStats {
+    /// Buffers that were handed out and have not come back yet, including
+    /// the ones that Dart still holds on to.
+    pub outstanding: usize,
+    /// How many times an idle buffer was handed out again.
+    pub recycled: usize,
+    /// How many buffers the pool allocated.
+    pub allocated: usize,
+}
+
+impl<T> BufferPool<T> {
+    /// Creates an empty pool.
+    pub fn new() -> Self {
+        Self {
+            state: Arc::new(Mutex::new(State {
+                idle: Vec::new(),
+                stats: PoolStats::default(),
+            })),
+        }
+    }
+
+    /// Hands out an empty buffer, that keeps the capacity it had the last
+    /// time it was used.
+    pub fn get(&self) -> PooledBuffer<T> {
+        let mut state = self.lock();
+        let buffer = match state.idle.pop() {
+            Some(buffer) => {
+                state.stats.recycled += 1;
+                buffer
+            },
+            None => {
+                state.stats.allocated += 1;
+                Vec::new()
+            },
+        };
+        state.stats.outstanding += 1;
+        PooledBuffer {
+            buffer,
+            pool: self.clone(),
+        }
+    }
+
+    /// Returns the current counters of the pool.
+    pub fn stats(&self) -> PoolStats {
+        self.lock().stats
+    }
+
+    /// Frees the buffers that are not in use.
+    pub fn shrink(&self) {
+        self.lock().idle = Vec::new();
+    }
+
+    fn lock(&self) -> MutexGuard<'_, State<T>> {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl<T> Clone for BufferPool<T> {
+    fn clone(&self) -> Self {
+        Self {
+            state: self.state.clone(),
+        }
+    }
+}
+
+impl<T> Default for BufferPool<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A buffer of a [`BufferPool`], it goes back to the pool when dropped.
+///
+/// Wrap it in a [`ZeroCopyBuffer`] to send it to Dart, it then goes back to
+/// the pool once Dart finalizes it.
+#[derive(Debug)]
+pub struct PooledBuffer<T> {
+    buffer: Vec<T>,
+    pool: BufferPool<T>,
+}
+
+impl<T> PooledBuffer<T> {
+    /// Takes the buffer out of the pool for good.
+    pub fn into_inner(self) -> Vec<T> {
+        // skips `Drop`, which would give the buffer back.
+        let mut this = ManuallyDrop::new(self);
+        this.pool.lock().stats.outstanding -= 1;
+        unsafe { ptr::drop_in_place(&mut this.pool) };
+        mem::take(&mut this.buffer)
+    }
+}
+
+impl<T> Drop for PooledBuffer<T> {
+    fn drop(&mut self) {
+        let mut buffer = mem::take(&mut self.buffer);
+        buffer.clear();
+        let mut state = self.pool.lock();
+        state.stats.outstanding -= 1;
+        state.idle.push(buffer);
+    }
+}
+
+impl<T> Deref for PooledBuffer<T> {
+    type Target = Vec<T>;
+
+    fn deref(&self) -> &Vec<T> {
+        &self.buffer
+    }
+}
+
+impl<T> DerefMut for PooledBuffer<T> {
+    fn deref_mut(&mut self) -> &mut Vec<T> {
+        &mut self.buffer
+    }
+}
+
+impl<T> AsRef<[T]> for PooledBuffer<T> {
+    fn as_ref(&self) -> &[T] {
+        &self.buffer
+    }
+}
+
+/// The buffer goes back to its pool once Dart finalizes it.
+impl<T> IntoDart for ZeroCopyBuffer<PooledBuffer<T>>
+where
+    T: DartTypedDataTypeTrait + Send + 'static,
+{
+    fn into_dart(self) -> DartCObject {
+        owner_to_dart_native_external_typed_data(
+            self.0,
+            T::dart_typed_data_type(),
+        )
+    }
+}
+
+impl<T> IntoDartExceptPrimitive for ZeroCopyBuffer<PooledBuffer<T>> where
+    T: DartTypedDataTypeTrait + Send + 'static
+{
+}
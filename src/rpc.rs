@@ -0,0 +1,308 @@
+//! Requests from Dart that Rust replies to, over ports.
+//!
+//! Dart posts requests to the [`NativePort`] of an [`RpcServer`], every
+//! request is handled by the handler registered for its method, and its
+//! reply is posted back to the `SendPort` of the request, tagged with the id
+//! of the request.
+//!
+//! The messages are lists:
+//! - a request is `[0, id, method, replyPort, payload]`, `id` and `method`
+//!   are `int`s and `replyPort` is a `SendPort`,
+//! - a cancellation is `[1, id]`, the handler of the request is aborted and
+//!   nothing is replied,
+//! - a reply is `[id, 0, result]` if the request succeeded and
+//!   `[id, 1, message]` if it failed, `message` is a `String`.
+//!
+//! The ids are chosen by Dart, they only have to be unique among the
+//! requests that were not replied to yet. A request with the id of a request
+//! in flight cancels that request.
+
+use std::{
+    collections::HashMap,
+    fmt,
+    future::Future,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex, MutexGuard, PoisonError,
+    },
+};
+
+use futures::future::{self, AbortHandle, Abortable, BoxFuture, FutureExt};
+
+use crate::{
+    ffi::{DartCObject, DartPort, ILLEGAL_PORT},
+    from_dart, FromDart, IntoDart, Isolate, NativePort, NativePortError,
+    SendPort,
+};
+
+const REQUEST: i64 = 0;
+const CANCEL: i64 = 1;
+const OK: i64 = 0;
+const ERR: i64 = 1;
+
+type Handler =
+    Box<dyn Fn(&DartCObject, Reply) -> BoxFuture<'static, ()> + Send + Sync>;
+
+type Spawn = Box<dyn Fn(BoxFuture<'static, ()>) + Send + Sync>;
+
+/// The handlers of an [`RpcServer`], by method.
+///
+/// #### Example
+/// ```rust,ignore
+/// # use allo_isolate::rpc::RpcRegistry;
+/// let server = RpcRegistry::new()
+///     .register(1, |(a, b): (i64, i64)| async move { Ok::<_, String>(a + b) })
+///     .register(2, |url: String| async move { fetch(&url).await })
+///     .serve("rpc", |task| { tokio::spawn(task); })?;
+/// // send `server.port()` to Dart.
+/// ```
+#[derive(Default)]
+pub struct RpcRegistry {
+    handlers: HashMap<i64, Handler>,
+}
+
+impl RpcRegistry {
+    /// Creates a registry without any handler.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Handles the requests for `method` with `handler`, which is called
+    /// with the payload of the request.
+    ///
+    /// The reply is the output of the future, a payload that can not be
+    /// decoded into `P` is replied to with an error right away.
+    pub fn register<P, F, Fut, R, E>(mut self, method: i64, handler: F) -> Self
+    where
+        P: FromDart,
+        F: Fn(P) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<R, E>> + Send + 'static,
+        R: IntoDart,
+        E: fmt::Display,
+    {
+        let handler = move |payload: &DartCObject, reply: Reply| {
+            let params = match P::from_dart(payload) {
+                Ok(params) => params,
+                Err(error) => {
+                    reply.err(error);
+                    return future::ready(()).boxed();
+                },
+            };
+            handler(params)
+                .map(move |result| match result {
+                    Ok(result) => reply.ok(result),
+                    Err(error) => reply.err(error),
+                })
+                .boxed()
+        };
+        self.handlers.insert(method, Box::new(handler));
+        self
+    }
+
+    /// Opens the port that Dart posts the requests to, the futures of the
+    /// handlers are run with `spawn`.
+    pub fn serve<S>(
+        self,
+        name: &str,
+        spawn: S,
+    ) -> Result<RpcServer, NativePortError>
+    where
+        S: Fn(BoxFuture<'static, ()>) + Send + Sync + 'static,
+    {
+        let state = Arc::new(State {
+            handlers: self.handlers,
+            spawn: Box::new(spawn),
+            in_flight: Mutex::new(HashMap::new()),
+            next_request: AtomicU64::new(0),
+        });
+        let port = NativePort::new(name, {
+            let state = state.clone();
+            move |msg| state.handle(msg)
+        })?;
+        Ok(RpcServer {
+            port: Some(port),
+            state,
+        })
+    }
+}
+
+impl fmt::Debug for RpcRegistry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RpcRegistry")
+            .field("methods", &self.handlers.keys())
+            .finish()
+    }
+}
+
+/// Handles the requests that Dart posts to its port, see [`RpcRegistry`].
+///
+/// The port is closed and every request in flight is aborted when the
+/// server is dropped.
+pub struct RpcServer {
+    /// Only `None` while the server is dropped.
+    port: Option<NativePort>,
+    state: Arc<State>,
+}
+
+impl RpcServer {
+    /// The port that Dart posts the requests to.
+    pub const fn port(&self) -> DartPort {
+        match &self.port {
+            Some(port) => port.port(),
+            None => ILLEGAL_PORT,
+        }
+    }
+
+    /// The number of requests that were not replied to yet.
+    pub fn in_flight(&self) -> usize {
+        self.state.in_flight().len()
+    }
+}
+
+impl fmt::Debug for RpcServer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RpcServer")
+            .field("port", &self.port())
+            .field("in_flight", &self.in_flight())
+            .finish()
+    }
+}
+
+impl Drop for RpcServer {
+    fn drop(&mut self) {
+        // no request can arrive once the port is closed.
+        drop(self.port.take());
+        self.state
+            .in_flight()
+            .drain()
+            .for_each(|(_, request)| request.task.abort());
+    }
+}
+
+struct State {
+    handlers: HashMap<i64, Handler>,
+    spawn: Spawn,
+    in_flight: Mutex<HashMap<i64, InFlight>>,
+    next_request: AtomicU64,
+}
+
+/// A request that was not replied to yet.
+struct InFlight {
+    /// Tells the request apart from the other requests with the same id.
+    request: u64,
+    task: AbortHandle,
+}
+
+impl State {
+    fn in_flight(&self) -> MutexGuard<'_, HashMap<i64, InFlight>> {
+        self.in_flight
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Forgets request `request` once it is done, unless its id was reused
+    /// by a newer request already.
+    fn finish(&self, id: i64, request: u64) {
+        let mut in_flight = self.in_flight();
+        if in_flight.get(&id).is_some_and(|r| r.request == request) {
+            in_flight.remove(&id);
+        }
+    }
+
+    /// Messages that do not follow the layout are dropped, there is nowhere
+    /// to reply to.
+    fn handle(self: &Arc<Self>, msg: &DartCObject) {
+        let Ok(items) = from_dart::array(msg) else {
+            return;
+        };
+        let items: Vec<_> = items.collect();
+        let Some(Ok(tag)) = items.first().map(|tag| i64::from_dart(tag)) else {
+            return;
+        };
+        match (tag, items.as_slice()) {
+            (REQUEST, [_, id, method, reply_port, payload]) => {
+                let (Ok(id), Ok(method), Ok(reply_port)) = (
+                    i64::from_dart(id),
+                    i64::from_dart(method),
+                    SendPort::from_dart(reply_port),
+                ) else {
+                    return;
+                };
+                self.request(id, method, reply_port, payload);
+            },
+            (CANCEL, [_, id]) => {
+                let Ok(id) = i64::from_dart(id) else {
+                    return;
+                };
+                if let Some(request) = self.in_flight().remove(&id) {
+                    request.task.abort();
+                }
+            },
+            _ => {},
+        }
+    }
+
+    fn request(
+        self: &Arc<Self>,
+        id: i64,
+        method: i64,
+        reply_port: SendPort,
+        payload: &DartCObject,
+    ) {
+        let mut reply = Reply {
+            isolate: reply_port.isolate(),
+            id,
+            request: None,
+        };
+        let Some(handler) = self.handlers.get(&method) else {
+            reply.err(format!("unknown method {}", method));
+            return;
+        };
+        let request = self.next_request.fetch_add(1, Ordering::Relaxed);
+        let (task, registration) = AbortHandle::new_pair();
+        let replaced = self.in_flight().insert(id, InFlight { request, task });
+        if let Some(replaced) = replaced {
+            replaced.task.abort();
+        }
+        reply.request = Some((self.clone(), request));
+        let reply = Abortable::new(handler(payload, reply), registration);
+        let state = self.clone();
+        (self.spawn)(
+            async move {
+                // an aborted request is not replied to.
+                let _ = reply.await;
+                // in case the handler dropped its reply.
+                state.finish(id, request);
+            }
+            .boxed(),
+        );
+    }
+}
+
+/// Where the reply of a request goes.
+struct Reply {
+    isolate: Isolate,
+    id: i64,
+    /// The request in flight, if it got that far.
+    request: Option<(Arc<State>, u64)>,
+}
+
+impl Reply {
+    fn ok(self, result: impl IntoDart) {
+        self.finish();
+        self.isolate.post((self.id, OK, result));
+    }
+
+    fn err(self, error: impl fmt::Display) {
+        self.finish();
+        self.isolate.post((self.id, ERR, error.to_string()));
+    }
+
+    /// The request is done before Dart gets its reply, so that Dart can
+    /// reuse its id right away.
+    fn finish(&self) {
+        if let Some((state, request)) = &self.request {
+            state.finish(self.id, *request);
+        }
+    }
+}
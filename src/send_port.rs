@@ -0,0 +1,118 @@
+use crate::{
+    ffi::{self, *},
+    FromDart, FromDartError, FromDartExceptPrimitive, IntoDart,
+    IntoDartExceptPrimitive, Isolate,
+};
+
+/// A Dart `SendPort`, so that a port can be handed to Dart inside a message,
+/// for example to tell it where to send the reply.
+///
+/// Dart receives it as a `SendPort`, and a `SendPort` received from Dart can
+/// be decoded with [`FromDart`] and posted to with [`SendPort::isolate`].
+///
+/// #### Example
+/// ```rust,ignore
+/// # use allo_isolate::{NativePort, SendPort};
+/// let replies = NativePort::new("replies", |msg| { /* .. */ })?;
+/// isolate.post(("ping", replies.send_port()));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SendPort {
+    /// The port that messages are sent to.
+    pub id: DartPort,
+    /// The port of the isolate that created the `SendPort`,
+    /// [`ILLEGAL_PORT`](ffi::ILLEGAL_PORT) for ports created outside of an
+    /// isolate, like a [`NativePort`](crate::NativePort).
+    pub origin_id: DartPort,
+}
+
+impl SendPort {
+    /// A `SendPort` to the port `id`, that was not created by an isolate.
+    pub const fn new(id: DartPort) -> Self {
+        Self {
+            id,
+            origin_id: ffi::ILLEGAL_PORT,
+        }
+    }
+
+    /// An [`Isolate`] that posts to this port.
+    pub const fn isolate(&self) -> Isolate {
+        Isolate::new(self.id)
+    }
+}
+
+/// A Dart `Capability`, an unforgeable object that Dart uses for example to
+/// pause and resume isolates.
+///
+/// Only Dart can create capabilities, Rust can receive them and send them
+/// back.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Capability {
+    /// The id of the capability.
+    pub id: i64,
+}
+
+impl IntoDart for SendPort {
+    fn into_dart(self) -> DartCObject {
+        DartCObject {
+            ty: DartCObjectType::DartSendPort,
+            value: DartCObjectValue {
+                as_send_port: DartNativeSendPort {
+                    id: self.id,
+                    origin_id: self.origin_id,
+                },
+            },
+        }
+    }
+}
+
+impl IntoDartExceptPrimitive for SendPort {}
+
+impl FromDart for SendPort {
+    fn from_dart(obj: &DartCObject) -> Result<Self, FromDartError> {
+        match obj.ty {
+            DartCObjectType::DartSendPort => {
+                let port = unsafe { obj.value.as_send_port };
+                Ok(Self {
+                    id: port.id,
+                    origin_id: port.origin_id,
+                })
+            },
+            _ => Err(FromDartError::UnexpectedType {
+                expected: "a SendPort",
+                found: obj.ty,
+            }),
+        }
+    }
+}
+
+impl FromDartExceptPrimitive for SendPort {}
+
+impl IntoDart for Capability {
+    fn into_dart(self) -> DartCObject {
+        DartCObject {
+            ty: DartCObjectType::DartCapability,
+            value: DartCObjectValue {
+                as_capability: DartNativeCapability { id: self.id },
+            },
+        }
+    }
+}
+
+impl IntoDartExceptPrimitive for Capability {}
+
+impl FromDart for Capability {
+    fn from_dart(obj: &DartCObject) -> Result<Self, FromDartError> {
+        match obj.ty {
+            DartCObjectType::DartCapability => Ok(Self {
+                id: unsafe { obj.value.as_capability.id },
+            }),
+            _ => Err(FromDartError::UnexpectedType {
+                expected: "a Capability",
+                found: obj.ty,
+            }),
+        }
+    }
+}
+
+impl FromDartExceptPrimitive for Capability {}
@@ -0,0 +1,627 @@
+use std::fmt;
+
+use serde::de::{
+    self, DeserializeSeed, Error as _, IntoDeserializer, Unexpected, Visitor,
+};
+
+use crate::{
+    ffi::{DartCObject, DartCObjectType, DartTypedDataType},
+    from_dart::{self, FromDartError},
+};
+
+/// Deserializes a `T` from a borrowed [`DartCObject`], for example a message
+/// that Dart posted to a [`NativePort`](crate::NativePort).
+///
+/// It expects the layout produced by [`to_dart`](crate::to_dart), strings and
+/// `Uint8List`s are borrowed from `obj` when `T` allows it.
+///
+/// #### Example
+/// ```rust,ignore
+/// # use allo_isolate::{from_dart, NativePort};
+/// #[derive(serde::Deserialize)]
+/// struct Point {
+///     x: f64,
+///     y: f64,
+/// }
+/// let port = NativePort::new("points", |msg| {
+///     match from_dart::<Point>(msg) {
+///         Ok(point) => println!("({}, {})", point.x, point.y),
+///         Err(e) => eprintln!("{}", e),
+///     }
+/// })?;
+/// ```
+pub fn from_dart<'de, T>(obj: &'de DartCObject) -> Result<T, Error>
+where
+    T: de::Deserialize<'de>,
+{
+    T::deserialize(Deserializer { obj })
+}
+
+/// The error returned when a value could not be deserialized, together with
+/// the path into the object graph where it happened.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Error {
+    message: String,
+    /// innermost segment first, segments are added as the error bubbles up.
+    path: Vec<Segment>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Segment {
+    Index(usize),
+    Field(&'static str),
+}
+
+impl Error {
+    /// The path to the object that could not be deserialized, for example
+    /// `.events[2].name`, or `.` for the root object.
+    pub fn path(&self) -> String {
+        if self.path.is_empty() {
+            return String::from(".");
+        }
+        let mut path = String::new();
+        for segment in self.path.iter().rev() {
+            match segment {
+                Segment::Index(index) => path += &format!("[{}]", index),
+                Segment::Field(field) => path += &format!(".{}", field),
+            }
+        }
+        path
+    }
+
+    /// The message describing what went wrong, without the path.
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    fn at(mut self, segment: Segment) -> Self {
+        self.path.push(segment);
+        self
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} at `{}`", self.message, self.path())
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl de::Error for Error {
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        Self {
+            message: msg.to_string(),
+            path: Vec::new(),
+        }
+    }
+}
+
+impl From<FromDartError> for Error {
+    fn from(e: FromDartError) -> Self {
+        Error::custom(e)
+    }
+}
+
+#[derive(Clone, Copy)]
+struct Deserializer<'de> {
+    obj: &'de DartCObject,
+}
+
+impl<'de> Deserializer<'de> {
+    fn unexpected(self) -> Unexpected<'de> {
+        match self.obj.ty {
+            DartCObjectType::DartNull => Unexpected::Unit,
+            DartCObjectType::DartBool => {
+                Unexpected::Bool(unsafe { self.obj.value.as_bool })
+            },
+            DartCObjectType::DartInt32 | DartCObjectType::DartInt64 => {
+                Unexpected::Signed(from_dart::int(self.obj).unwrap_or(0))
+            },
+            DartCObjectType::DartDouble => {
+                Unexpected::Float(unsafe { self.obj.value.as_double })
+            },
+            DartCObjectType::DartString => match from_dart::str(self.obj) {
+                Ok(s) => Unexpected::Str(s),
+                Err(_) => Unexpected::Other("a String"),
+            },
+            DartCObjectType::DartArray => Unexpected::Seq,
+            DartCObjectType::DartTypedData
+            | DartCObjectType::DartExternalTypedData
+            | DartCObjectType::DartUnmodifiableExternalTypedData => {
+                Unexpected::Other("typed data")
+            },
+            _ => Unexpected::Other("an unsupported object"),
+        }
+    }
+
+    fn invalid_type(self, exp: &dyn de::Expected) -> Error {
+        Error::invalid_type(self.unexpected(), exp)
+    }
+
+    fn items(
+        self,
+        exp: &dyn de::Expected,
+    ) -> Result<Vec<&'de DartCObject>, Error> {
+        match from_dart::array(self.obj) {
+            Ok(items) => Ok(items.collect()),
+            Err(_) => Err(self.invalid_type(exp)),
+        }
+    }
+
+    const fn typed_data_type(&self) -> Option<DartTypedDataType> {
+        match self.obj.ty {
+            DartCObjectType::DartTypedData => {
+                Some(unsafe { self.obj.value.as_typed_data.ty })
+            },
+            DartCObjectType::DartExternalTypedData
+            | DartCObjectType::DartUnmodifiableExternalTypedData => {
+                Some(unsafe { self.obj.value.as_external_typed_data.ty })
+            },
+            _ => None,
+        }
+    }
+
+    /// The bytes of a `Uint8List`, a `Uint8ClampedList` or a `ByteData`.
+    fn bytes(&self) -> Option<&'de [u8]> {
+        match self.typed_data_type()? {
+            ty @ (DartTypedDataType::Uint8
+            | DartTypedDataType::Uint8Clamped
+            | DartTypedDataType::ByteData) => {
+                from_dart::typed_data_of::<u8>(self.obj, ty).ok()
+            },
+            _ => None,
+        }
+    }
+
+    // typed data is a sequence of numbers, only `deserialize_bytes` and
+    // `deserialize_byte_buf` borrow the bytes of byte lists.
+    fn visit_typed_data<V: Visitor<'de>>(
+        self,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        let Some(ty) = self.typed_data_type() else {
+            return Err(self.invalid_type(&visitor));
+        };
+        match ty {
+            DartTypedDataType::Uint8
+            | DartTypedDataType::Uint8Clamped
+            | DartTypedDataType::ByteData => {
+                self.visit_elements::<u8, _>(ty, visitor)
+            },
+            DartTypedDataType::Int8 => {
+                self.visit_elements::<i8, _>(ty, visitor)
+            },
+            DartTypedDataType::Int16 => {
+                self.visit_elements::<i16, _>(ty, visitor)
+            },
+            DartTypedDataType::Uint16 => {
+                self.visit_elements::<u16, _>(ty, visitor)
+            },
+            DartTypedDataType::Int32 => {
+                self.visit_elements::<i32, _>(ty, visitor)
+            },
+            DartTypedDataType::Uint32 => {
+                self.visit_elements::<u32, _>(ty, visitor)
+            },
+            DartTypedDataType::Int64 => {
+                self.visit_elements::<i64, _>(ty, visitor)
+            },
+            DartTypedDataType::Uint64 => {
+                self.visit_elements::<u64, _>(ty, visitor)
+            },
+            DartTypedDataType::Float32 => {
+                self.visit_elements::<f32, _>(ty, visitor)
+            },
+            DartTypedDataType::Float64 => {
+                self.visit_elements::<f64, _>(ty, visitor)
+            },
+            _ => Err(Error::custom(format!(
+                "unsupported typed data of {:?}",
+                ty
+            ))),
+        }
+    }
+
+    fn visit_elements<T, V>(
+        self,
+        ty: DartTypedDataType,
+        visitor: V,
+    ) -> Result<V::Value, Error>
+    where
+        T: IntoDeserializer<'de, Error> + Copy,
+        V: Visitor<'de>,
+    {
+        let elements = from_dart::typed_data_of::<T>(self.obj, ty)?;
+        visitor.visit_seq(SeqAccess {
+            items: elements.iter().map(|e| e.into_deserializer()),
+            index: 0,
+            offset: 0,
+            fields: &[],
+        })
+    }
+
+    fn visit_items<V: Visitor<'de>>(
+        self,
+        items: &[&'de DartCObject],
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        visitor.visit_seq(SeqAccess {
+            items: items.iter().map(|&obj| Deserializer { obj }),
+            index: 0,
+            offset: 0,
+            fields,
+        })
+    }
+}
+
+impl<'de> de::Deserializer<'de> for Deserializer<'de> {
+    type Error = Error;
+
+    fn deserialize_any<V: Visitor<'de>>(
+        self,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        match self.obj.ty {
+            DartCObjectType::DartNull => visitor.visit_unit(),
+            DartCObjectType::DartBool => {
+                visitor.visit_bool(unsafe { self.obj.value.as_bool })
+            },
+            DartCObjectType::DartInt32 => {
+                visitor.visit_i32(unsafe { self.obj.value.as_int32 })
+            },
+            DartCObjectType::DartInt64 => {
+                visitor.visit_i64(unsafe { self.obj.value.as_int64 })
+            },
+            DartCObjectType::DartDouble => {
+                visitor.visit_f64(unsafe { self.obj.value.as_double })
+            },
+            DartCObjectType::DartString => {
+                visitor.visit_borrowed_str(from_dart::str(self.obj)?)
+            },
+            DartCObjectType::DartArray => {
+                let items = self.items(&visitor)?;
+                self.visit_items(&items, &[], visitor)
+            },
+            DartCObjectType::DartTypedData
+            | DartCObjectType::DartExternalTypedData
+            | DartCObjectType::DartUnmodifiableExternalTypedData => {
+                self.visit_typed_data(visitor)
+            },
+            _ => Err(self.invalid_type(&visitor)),
+        }
+    }
+
+    // `u64` is sent as the `i64` with the same bits.
+    fn deserialize_u64<V: Visitor<'de>>(
+        self,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        match from_dart::int(self.obj) {
+            Ok(v) => visitor.visit_u64(v as u64),
+            Err(_) => self.deserialize_any(visitor),
+        }
+    }
+
+    // 128 bit integers are sent as strings.
+    fn deserialize_i128<V: Visitor<'de>>(
+        self,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        match from_dart::str(self.obj) {
+            Ok(s) => match s.parse() {
+                Ok(v) => visitor.visit_i128(v),
+                Err(_) => {
+                    Err(Error::invalid_value(Unexpected::Str(s), &visitor))
+                },
+            },
+            Err(_) => self.deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_u128<V: Visitor<'de>>(
+        self,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        match from_dart::str(self.obj) {
+            Ok(s) => match s.parse() {
+                Ok(v) => visitor.visit_u128(v),
+                Err(_) => {
+                    Err(Error::invalid_value(Unexpected::Str(s), &visitor))
+                },
+            },
+            Err(_) => self.deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_char<V: Visitor<'de>>(
+        self,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        match from_dart::int(self.obj) {
+            Ok(v) => match u32::try_from(v).ok().and_then(char::from_u32) {
+                Some(c) => visitor.visit_char(c),
+                None => {
+                    Err(Error::invalid_value(Unexpected::Signed(v), &visitor))
+                },
+            },
+            Err(_) => self.deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_bytes<V: Visitor<'de>>(
+        self,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        match self.bytes() {
+            Some(bytes) => visitor.visit_borrowed_bytes(bytes),
+            None => self.deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_byte_buf<V: Visitor<'de>>(
+        self,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        self.deserialize_bytes(visitor)
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(
+        self,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        match self.obj.ty {
+            DartCObjectType::DartNull => visitor.visit_none(),
+            _ => visitor.visit_some(self),
+        }
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        let items = self.items(&visitor)?;
+        self.visit_items(&items, fields, visitor)
+    }
+
+    fn deserialize_map<V: Visitor<'de>>(
+        self,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        let items = self.items(&visitor)?;
+        visitor.visit_map(MapAccess {
+            items: items.into_iter(),
+            index: 0,
+            value: None,
+        })
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        let items = self.items(&visitor)?;
+        let (index, fields) = match items.split_first() {
+            Some((index, fields)) => (*index, fields.to_vec()),
+            None => return Err(Error::invalid_length(0, &"a variant index")),
+        };
+        let index = from_dart::int(index)
+            .ok()
+            .and_then(|index| u32::try_from(index).ok())
+            .filter(|&index| (index as usize) < variants.len())
+            .ok_or_else(|| {
+                Deserializer { obj: index }
+                    .invalid_type(&"a variant index")
+                    .at(Segment::Index(0))
+            })?;
+        visitor.visit_enum(EnumAccess { index, fields })
+    }
+
+    fn deserialize_ignored_any<V: Visitor<'de>>(
+        self,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        visitor.visit_unit()
+    }
+
+    fn is_human_readable(&self) -> bool {
+        false
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 u8 u16 u32 f32 f64 str string
+        unit unit_struct seq tuple tuple_struct identifier
+    }
+}
+
+struct SeqAccess<I> {
+    items: I,
+    index: usize,
+    /// Added to `index` in the path of an error.
+    offset: usize,
+    fields: &'static [&'static str],
+}
+
+impl<'de, I, D> de::SeqAccess<'de> for SeqAccess<I>
+where
+    I: ExactSizeIterator<Item = D>,
+    D: de::Deserializer<'de, Error = Error>,
+{
+    type Error = Error;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(
+        &mut self,
+        seed: T,
+    ) -> Result<Option<T::Value>, Error> {
+        let item = match self.items.next() {
+            Some(item) => item,
+            None => return Ok(None),
+        };
+        let segment = match self.fields.get(self.index) {
+            Some(field) => Segment::Field(field),
+            None => Segment::Index(self.offset + self.index),
+        };
+        self.index += 1;
+        seed.deserialize(item).map(Some).map_err(|e| e.at(segment))
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.items.len())
+    }
+}
+
+struct MapAccess<'de, I> {
+    items: I,
+    index: usize,
+    value: Option<&'de DartCObject>,
+}
+
+impl<'de, I> de::MapAccess<'de> for MapAccess<'de, I>
+where
+    I: ExactSizeIterator<Item = &'de DartCObject>,
+{
+    type Error = Error;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, Error> {
+        let pair = match self.items.next() {
+            Some(pair) => pair,
+            None => return Ok(None),
+        };
+        let index = self.index;
+        self.index += 1;
+        let (key, value) =
+            pair_of(pair).map_err(|e| e.at(Segment::Index(index)))?;
+        self.value = Some(value);
+        seed.deserialize(Deserializer { obj: key })
+            .map(Some)
+            .map_err(|e| e.at(Segment::Index(0)).at(Segment::Index(index)))
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(
+        &mut self,
+        seed: V,
+    ) -> Result<V::Value, Error> {
+        let value = self
+            .value
+            .take()
+            .ok_or_else(|| Error::custom("value is missing"))?;
+        seed.deserialize(Deserializer { obj: value }).map_err(|e| {
+            e.at(Segment::Index(1)).at(Segment::Index(self.index - 1))
+        })
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.items.len())
+    }
+}
+
+fn pair_of(obj: &DartCObject) -> Result<(&DartCObject, &DartCObject), Error> {
+    let exp = &"a [key, value] pair";
+    match (Deserializer { obj }).items(exp)?[..] {
+        [key, value] => Ok((key, value)),
+        ref items => Err(Error::invalid_length(items.len(), exp)),
+    }
+}
+
+struct EnumAccess<'de> {
+    index: u32,
+    fields: Vec<&'de DartCObject>,
+}
+
+impl<'de> de::EnumAccess<'de> for EnumAccess<'de> {
+    type Error = Error;
+    type Variant = VariantAccess<'de>;
+
+    fn variant_seed<V: DeserializeSeed<'de>>(
+        self,
+        seed: V,
+    ) -> Result<(V::Value, Self::Variant), Error> {
+        let variant = seed.deserialize(
+            IntoDeserializer::<Error>::into_deserializer(self.index),
+        )?;
+        Ok((
+            variant,
+            VariantAccess {
+                fields: self.fields,
+            },
+        ))
+    }
+}
+
+struct VariantAccess<'de> {
+    fields: Vec<&'de DartCObject>,
+}
+
+impl<'de> VariantAccess<'de> {
+    fn visit_fields<V: Visitor<'de>>(
+        self,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        // the variant index is the first item of the list.
+        visitor.visit_seq(SeqAccess {
+            items: self.fields.into_iter().map(|obj| Deserializer { obj }),
+            index: 0,
+            offset: 1,
+            fields,
+        })
+    }
+}
+
+impl<'de> de::VariantAccess<'de> for VariantAccess<'de> {
+    type Error = Error;
+
+    fn unit_variant(self) -> Result<(), Error> {
+        match self.fields.len() {
+            0 => Ok(()),
+            len => Err(Error::invalid_length(len + 1, &"a unit variant")),
+        }
+    }
+
+    fn newtype_variant_seed<T: DeserializeSeed<'de>>(
+        self,
+        seed: T,
+    ) -> Result<T::Value, Error> {
+        match self.fields[..] {
+            [obj] => seed
+                .deserialize(Deserializer { obj })
+                .map_err(|e| e.at(Segment::Index(1))),
+            ref fields => Err(Error::invalid_length(
+                fields.len() + 1,
+                &"a newtype variant",
+            )),
+        }
+    }
+
+    fn tuple_variant<V: Visitor<'de>>(
+        self,
+        _len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        self.visit_fields(&[], visitor)
+    }
+
+    fn struct_variant<V: Visitor<'de>>(
+        self,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        self.visit_fields(fields, visitor)
+    }
+}
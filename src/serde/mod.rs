@@ -0,0 +1,31 @@
+//! serde support
+//!
+//! Any [`Serialize`](::serde::Serialize) type can be turned into a
+//! [`DartCObject`](crate::ffi::DartCObject) with [`to_dart`], or posted
+//! directly when wrapped in [`Serde`]. Received messages are read back into
+//! any [`Deserialize`](::serde::Deserialize) type with [`from_dart`].
+
+mod de;
+mod ser;
+
+pub use de::{from_dart, Error as DeserializeError};
+pub use ser::{to_dart, Error as SerializeError};
+
+use crate::{ffi::DartCObject, IntoDart, IntoDartExceptPrimitive};
+
+/// Wrapping a [`Serialize`](::serde::Serialize) type in this tuple struct
+/// will allow `into_dart()` to send it using its serde implementation, see
+/// [`to_dart`].
+///
+/// If it can not be serialized, the error message is sent instead, the same
+/// way an `Err` of a `Result` is sent.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Serde<T>(pub T);
+
+impl<T: ::serde::Serialize> IntoDart for Serde<T> {
+    fn into_dart(self) -> DartCObject {
+        to_dart(&self.0).into_dart()
+    }
+}
+
+impl<T: ::serde::Serialize> IntoDartExceptPrimitive for Serde<T> {}
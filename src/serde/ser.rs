@@ -0,0 +1,427 @@
+use std::{fmt, mem};
+
+use serde::ser::{self, Serialize};
+
+use crate::{
+    dart_array::DartArray,
+    ffi::{self, DartCObject},
+    IntoDart,
+};
+
+/// Serializes `value` into a [`DartCObject`].
+///
+/// The layout is the same as the one of the `IntoDart` implementations:
+/// - structs, tuples and sequences are sent as a `List`, struct fields in
+///   the order they are declared in.
+/// - maps are sent as a `List` of `[key, value]` pairs, like `HashMap`.
+/// - enum variants are sent as a `List` holding the index of the variant
+///   followed by its fields, like `#[derive(IntoDart)]` does.
+/// - bytes are sent as an `Uint8List`, `()`, unit structs and `None` as
+///   `null`.
+///
+/// #### Example
+/// ```rust,ignore
+/// # use allo_isolate::{to_dart, Isolate};
+/// #[derive(serde::Serialize)]
+/// struct Point {
+///     x: f64,
+///     y: f64,
+/// }
+/// let isolate = Isolate::new(42);
+/// isolate.post(to_dart(&Point { x: 1.0, y: 2.0 })?);
+/// ```
+pub fn to_dart<T>(value: &T) -> Result<DartCObject, Error>
+where
+    T: Serialize + ?Sized,
+{
+    value.serialize(Serializer)
+}
+
+/// The error returned when a value could not be serialized.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Error(String);
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl ser::Error for Error {
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        Self(msg.to_string())
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Serializer;
+
+/// The objects of a `List` that is being built.
+///
+/// If serializing fails half way, the external typed data that was already
+/// created is released when this is dropped, as nobody else will.
+struct Items(Vec<DartCObject>);
+
+impl Items {
+    fn new(variant_index: Option<u32>, len: Option<usize>) -> Self {
+        let mut items = Vec::with_capacity(
+            len.unwrap_or_default() + variant_index.is_some() as usize,
+        );
+        if let Some(index) = variant_index {
+            items.push((index as i32).into_dart());
+        }
+        Self(items)
+    }
+
+    fn push<T>(&mut self, value: &T) -> Result<(), Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        self.0.push(value.serialize(Serializer)?);
+        Ok(())
+    }
+
+    fn into_dart(mut self) -> DartCObject {
+        DartArray::from(mem::take(&mut self.0).into_iter()).into_dart()
+    }
+}
+
+impl Drop for Items {
+    fn drop(&mut self) {
+        for item in &self.0 {
+            unsafe { ffi::run_destructors(item) };
+        }
+    }
+}
+
+struct SerializeList(Items);
+
+struct SerializeMap {
+    pairs: Items,
+    key: Option<Items>,
+}
+
+impl ser::Serializer for Serializer {
+    type Error = Error;
+    type Ok = DartCObject;
+    type SerializeMap = SerializeMap;
+    type SerializeSeq = SerializeList;
+    type SerializeStruct = SerializeList;
+    type SerializeStructVariant = SerializeList;
+    type SerializeTuple = SerializeList;
+    type SerializeTupleStruct = SerializeList;
+    type SerializeTupleVariant = SerializeList;
+
+    fn is_human_readable(&self) -> bool {
+        false
+    }
+
+    fn serialize_bool(self, v: bool) -> Result<DartCObject, Error> {
+        Ok(v.into_dart())
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<DartCObject, Error> {
+        Ok(v.into_dart())
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<DartCObject, Error> {
+        Ok(v.into_dart())
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<DartCObject, Error> {
+        Ok(v.into_dart())
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<DartCObject, Error> {
+        Ok(v.into_dart())
+    }
+
+    fn serialize_i128(self, v: i128) -> Result<DartCObject, Error> {
+        Ok(v.into_dart())
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<DartCObject, Error> {
+        Ok(v.into_dart())
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<DartCObject, Error> {
+        Ok(v.into_dart())
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<DartCObject, Error> {
+        Ok(v.into_dart())
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<DartCObject, Error> {
+        Ok(v.into_dart())
+    }
+
+    fn serialize_u128(self, v: u128) -> Result<DartCObject, Error> {
+        Ok(v.into_dart())
+    }
+
+    fn serialize_f32(self, v: f32) -> Result<DartCObject, Error> {
+        Ok(v.into_dart())
+    }
+
+    fn serialize_f64(self, v: f64) -> Result<DartCObject, Error> {
+        Ok(v.into_dart())
+    }
+
+    fn serialize_char(self, v: char) -> Result<DartCObject, Error> {
+        Ok(v.into_dart())
+    }
+
+    fn serialize_str(self, v: &str) -> Result<DartCObject, Error> {
+        Ok(v.into_dart())
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<DartCObject, Error> {
+        Ok(v.to_vec().into_dart())
+    }
+
+    fn serialize_none(self) -> Result<DartCObject, Error> {
+        Ok(().into_dart())
+    }
+
+    fn serialize_some<T>(self, value: &T) -> Result<DartCObject, Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<DartCObject, Error> {
+        Ok(().into_dart())
+    }
+
+    fn serialize_unit_struct(
+        self,
+        _name: &'static str,
+    ) -> Result<DartCObject, Error> {
+        Ok(().into_dart())
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        variant_index: u32,
+        _variant: &'static str,
+    ) -> Result<DartCObject, Error> {
+        Ok(Items::new(Some(variant_index), None).into_dart())
+    }
+
+    fn serialize_newtype_struct<T>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<DartCObject, Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T>(
+        self,
+        _name: &'static str,
+        variant_index: u32,
+        _variant: &'static str,
+        value: &T,
+    ) -> Result<DartCObject, Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        let mut items = Items::new(Some(variant_index), Some(1));
+        items.push(value)?;
+        Ok(items.into_dart())
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> Result<SerializeList, Error> {
+        Ok(SerializeList(Items::new(None, len)))
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<SerializeList, Error> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        len: usize,
+    ) -> Result<SerializeList, Error> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        variant_index: u32,
+        _variant: &'static str,
+        len: usize,
+    ) -> Result<SerializeList, Error> {
+        Ok(SerializeList(Items::new(Some(variant_index), Some(len))))
+    }
+
+    fn serialize_map(self, len: Option<usize>) -> Result<SerializeMap, Error> {
+        Ok(SerializeMap {
+            pairs: Items::new(None, len),
+            key: None,
+        })
+    }
+
+    fn serialize_struct(
+        self,
+        _name: &'static str,
+        len: usize,
+    ) -> Result<SerializeList, Error> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        variant_index: u32,
+        _variant: &'static str,
+        len: usize,
+    ) -> Result<SerializeList, Error> {
+        Ok(SerializeList(Items::new(Some(variant_index), Some(len))))
+    }
+}
+
+impl ser::SerializeSeq for SerializeList {
+    type Error = Error;
+    type Ok = DartCObject;
+
+    fn serialize_element<T>(&mut self, value: &T) -> Result<(), Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        self.0.push(value)
+    }
+
+    fn end(self) -> Result<DartCObject, Error> {
+        Ok(self.0.into_dart())
+    }
+}
+
+impl ser::SerializeTuple for SerializeList {
+    type Error = Error;
+    type Ok = DartCObject;
+
+    fn serialize_element<T>(&mut self, value: &T) -> Result<(), Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        self.0.push(value)
+    }
+
+    fn end(self) -> Result<DartCObject, Error> {
+        Ok(self.0.into_dart())
+    }
+}
+
+impl ser::SerializeTupleStruct for SerializeList {
+    type Error = Error;
+    type Ok = DartCObject;
+
+    fn serialize_field<T>(&mut self, value: &T) -> Result<(), Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        self.0.push(value)
+    }
+
+    fn end(self) -> Result<DartCObject, Error> {
+        Ok(self.0.into_dart())
+    }
+}
+
+impl ser::SerializeTupleVariant for SerializeList {
+    type Error = Error;
+    type Ok = DartCObject;
+
+    fn serialize_field<T>(&mut self, value: &T) -> Result<(), Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        self.0.push(value)
+    }
+
+    fn end(self) -> Result<DartCObject, Error> {
+        Ok(self.0.into_dart())
+    }
+}
+
+impl ser::SerializeStruct for SerializeList {
+    type Error = Error;
+    type Ok = DartCObject;
+
+    fn serialize_field<T>(
+        &mut self,
+        _key: &'static str,
+        value: &T,
+    ) -> Result<(), Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        self.0.push(value)
+    }
+
+    fn end(self) -> Result<DartCObject, Error> {
+        Ok(self.0.into_dart())
+    }
+}
+
+impl ser::SerializeStructVariant for SerializeList {
+    type Error = Error;
+    type Ok = DartCObject;
+
+    fn serialize_field<T>(
+        &mut self,
+        _key: &'static str,
+        value: &T,
+    ) -> Result<(), Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        self.0.push(value)
+    }
+
+    fn end(self) -> Result<DartCObject, Error> {
+        Ok(self.0.into_dart())
+    }
+}
+
+impl ser::SerializeMap for SerializeMap {
+    type Error = Error;
+    type Ok = DartCObject;
+
+    fn serialize_key<T>(&mut self, key: &T) -> Result<(), Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        let mut pair = Items::new(None, Some(2));
+        pair.push(key)?;
+        self.key = Some(pair);
+        Ok(())
+    }
+
+    fn serialize_value<T>(&mut self, value: &T) -> Result<(), Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        let mut pair = self.key.take().ok_or_else(|| {
+            ser::Error::custom("serialize_value called before serialize_key")
+        })?;
+        pair.push(value)?;
+        self.pairs.0.push(pair.into_dart());
+        Ok(())
+    }
+
+    fn end(self) -> Result<DartCObject, Error> {
+        Ok(self.pairs.into_dart())
+    }
+}
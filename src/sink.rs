@@ -0,0 +1,106 @@
+//! A [`Sink`] that posts every item to an [`Isolate`].
+
+use std::{
+    fmt,
+    marker::PhantomData,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use futures::Sink;
+
+use crate::{IntoDart, Isolate, PostError};
+
+/// A [`Sink`] that posts every item it gets to the [`Isolate`], so streams can
+/// be forwarded to Dart with `StreamExt::forward` or `SinkExt::send_all`.
+///
+/// Once an item could not be posted, the error is kept and returned again by
+/// `poll_ready`, so nothing else is sent to a closed port.
+///
+/// #### Example
+/// ```rust,ignore
+/// # use allo_isolate::Isolate;
+/// use futures::StreamExt;
+/// let isolate = Isolate::new(42);
+/// futures::stream::iter(0..10).map(Ok).forward(isolate.sink()).await?;
+/// ```
+// a `Copy` sink would make it too easy to lose track of a closed port.
+#[allow(missing_copy_implementations)]
+pub struct IsolateSink<T> {
+    isolate: Isolate,
+    error: Option<PostError>,
+    _item: PhantomData<fn(T)>,
+}
+
+impl<T> IsolateSink<T> {
+    /// Create a new `IsolateSink` posting to `isolate`.
+    pub const fn new(isolate: Isolate) -> Self {
+        Self {
+            isolate,
+            error: None,
+            _item: PhantomData,
+        }
+    }
+
+    /// The [`Isolate`] the items are posted to.
+    pub const fn isolate(&self) -> Isolate {
+        self.isolate
+    }
+}
+
+impl<T> Clone for IsolateSink<T> {
+    fn clone(&self) -> Self {
+        Self {
+            isolate: self.isolate,
+            error: self.error,
+            _item: PhantomData,
+        }
+    }
+}
+
+impl<T> fmt::Debug for IsolateSink<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("IsolateSink")
+            .field("isolate", &self.isolate)
+            .field("error", &self.error)
+            .finish()
+    }
+}
+
+impl<T: IntoDart> Sink<T> for IsolateSink<T> {
+    type Error = PostError;
+
+    fn poll_ready(
+        self: Pin<&mut Self>,
+        _cx: &mut Context<'_>,
+    ) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(self.error.map_or(Ok(()), Err))
+    }
+
+    fn start_send(
+        mut self: Pin<&mut Self>,
+        item: T,
+    ) -> Result<(), Self::Error> {
+        if let Some(error) = self.error {
+            return Err(error);
+        }
+        let result = self.isolate.try_post(item);
+        self.error = result.err();
+        result
+    }
+
+    fn poll_flush(
+        self: Pin<&mut Self>,
+        _cx: &mut Context<'_>,
+    ) -> Poll<Result<(), Self::Error>> {
+        // items are posted right away, there is nothing to flush.
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_close(
+        self: Pin<&mut Self>,
+        _cx: &mut Context<'_>,
+    ) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+}
@@ -0,0 +1,294 @@
+//! A mock of the Dart VM, so that code posting to an [`Isolate`] can be unit
+//! tested without Dart.
+//!
+//! [`install`] hands the mock to this crate in place of `Dart_PostCObject`,
+//! `Dart_NewNativePort` and `Dart_CloseNativePort`. Messages posted to a
+//! [`ReceivePort`] are then decoded into [`DartValue`]s and kept until they
+//! are received.
+//!
+//! Like the real VM, the mock copies every message and only takes ownership
+//! of external typed data (for example a [`ZeroCopyBuffer`]) and native
+//! pointers (a [`NativeHandle`]). Their finalizers run once the mock
+//! "collects garbage", see [`gc`], or when the port that received them is
+//! closed.
+//!
+//! #### Example
+//! ```
+//! use allo_isolate::{
+//!     testing::{self, ReceivePort},
+//!     DartValue,
+//! };
+//!
+//! testing::install();
+//! let port = ReceivePort::new();
+//! assert!(port.isolate().post(vec![String::from("Dart")]));
+//! assert_eq!(
+//!     port.recv(),
+//!     Some(DartValue::List(vec![DartValue::from("Dart")]))
+//! );
+//! ```
+//!
+//! [`ZeroCopyBuffer`]: crate::ZeroCopyBuffer
+//! [`NativeHandle`]: crate::NativeHandle
+
+use std::{
+    collections::{BTreeMap, VecDeque},
+    ffi::{c_char, c_void},
+    ptr,
+    sync::{Mutex, MutexGuard, PoisonError},
+};
+
+use crate::{
+    ffi::{
+        self, DartCObject, DartCObjectType, DartHandleFinalizer,
+        DartNativeMessageHandler, DartPort,
+    },
+    from_dart, DartValue, FromDart, FromDartError, IntoDart, Isolate,
+};
+
+static VM: Mutex<Vm> = Mutex::new(Vm::new());
+
+struct Vm {
+    next_port: DartPort,
+    ports: BTreeMap<DartPort, Port>,
+    native_ports: BTreeMap<DartPort, DartNativeMessageHandler>,
+    /// finalizers of received messages that are not referenced anymore.
+    garbage: Vec<Finalizer>,
+}
+
+#[derive(Default)]
+struct Port {
+    messages: VecDeque<(DartValue, Vec<Finalizer>)>,
+}
+
+struct Finalizer {
+    callback: DartHandleFinalizer,
+    peer: *mut c_void,
+}
+
+// the peer is owned by the finalizer, which is only called once.
+unsafe impl Send for Finalizer {}
+
+impl Finalizer {
+    fn run(self) {
+        unsafe { (self.callback)(ptr::null_mut(), self.peer) }
+    }
+}
+
+impl Vm {
+    const fn new() -> Self {
+        Self {
+            next_port: ffi::ILLEGAL_PORT,
+            ports: BTreeMap::new(),
+            native_ports: BTreeMap::new(),
+            garbage: Vec::new(),
+        }
+    }
+
+    fn lock() -> MutexGuard<'static, Self> {
+        VM.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    const fn next_port(&mut self) -> DartPort {
+        self.next_port += 1;
+        self.next_port
+    }
+}
+
+/// Installs the mock VM, so that posting to an [`Isolate`] and opening a
+/// [`NativePort`](crate::NativePort) go through it.
+///
+/// Installing it more than once does nothing.
+pub fn install() {
+    unsafe {
+        crate::store_dart_post_cobject(post_cobject);
+        crate::store_dart_post_integer(post_integer);
+        crate::store_dart_new_native_port(new_native_port);
+        crate::store_dart_close_native_port(close_native_port);
+    }
+}
+
+/// Runs the finalizers of the external typed data and native pointers in
+/// messages already received from any [`ReceivePort`].
+///
+/// Returns the number of finalizers that ran.
+pub fn gc() -> usize {
+    let garbage = std::mem::take(&mut Vm::lock().garbage);
+    let collected = garbage.len();
+    garbage.into_iter().for_each(Finalizer::run);
+    collected
+}
+
+/// A port of the mock VM that records every message posted to it, the same
+/// as a `ReceivePort` on the Dart side.
+///
+/// The port is closed when it is dropped.
+#[derive(Debug)]
+pub struct ReceivePort {
+    port: DartPort,
+}
+
+impl ReceivePort {
+    /// Opens a new port.
+    pub fn new() -> Self {
+        let mut vm = Vm::lock();
+        let port = vm.next_port();
+        vm.ports.insert(port, Port::default());
+        Self { port }
+    }
+
+    /// The id of the port, as Dart would send it to Rust.
+    pub const fn port(&self) -> DartPort {
+        self.port
+    }
+
+    /// An [`Isolate`] that posts to this port.
+    pub const fn isolate(&self) -> Isolate {
+        Isolate::new(self.port)
+    }
+
+    /// Receives the oldest message that was posted to this port.
+    ///
+    /// The external typed data and native pointers of the message are handed
+    /// to the garbage collector, see [`gc`].
+    pub fn recv(&self) -> Option<DartValue> {
+        let mut vm = Vm::lock();
+        let vm = &mut *vm;
+        let port = vm.ports.get_mut(&self.port)?;
+        let (message, mut finalizers) = port.messages.pop_front()?;
+        vm.garbage.append(&mut finalizers);
+        Some(message)
+    }
+
+    /// Receives every message that was posted to this port so far.
+    pub fn messages(&self) -> Vec<DartValue> {
+        std::iter::from_fn(|| self.recv()).collect()
+    }
+
+    /// Closes the port, posting to it fails from now on, the same way it
+    /// does after Dart closed a `ReceivePort`.
+    ///
+    /// Messages that were not received yet are dropped and their finalizers
+    /// run. Returns `false` if the port was already closed.
+    pub fn close(&self) -> bool {
+        let port = Vm::lock().ports.remove(&self.port);
+        match port {
+            Some(port) => {
+                port.messages
+                    .into_iter()
+                    .flat_map(|(_, finalizers)| finalizers)
+                    .for_each(Finalizer::run);
+                true
+            },
+            None => false,
+        }
+    }
+
+    /// Whether the port was closed.
+    pub fn is_closed(&self) -> bool {
+        !Vm::lock().ports.contains_key(&self.port)
+    }
+}
+
+impl Default for ReceivePort {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for ReceivePort {
+    fn drop(&mut self) {
+        self.close();
+    }
+}
+
+/// Copies `obj`, the finalizers of its external typed data and native
+/// pointers are pushed to `finalizers`.
+fn decode(
+    obj: &DartCObject,
+    finalizers: &mut Vec<Finalizer>,
+) -> Result<DartValue, FromDartError> {
+    let value = DartValue::from_dart(obj)?;
+    collect_finalizers(obj, finalizers);
+    Ok(value)
+}
+
+fn collect_finalizers(obj: &DartCObject, finalizers: &mut Vec<Finalizer>) {
+    match obj.ty {
+        DartCObjectType::DartExternalTypedData
+        | DartCObjectType::DartUnmodifiableExternalTypedData => {
+            let data = unsafe { obj.value.as_external_typed_data };
+            finalizers.push(Finalizer {
+                callback: data.callback,
+                peer: data.peer,
+            });
+        },
+        DartCObjectType::DartNativePointer => {
+            let pointer = unsafe { obj.value.as_native_pointer };
+            finalizers.push(Finalizer {
+                callback: pointer.callback,
+                peer: pointer.ptr as *mut c_void,
+            });
+        },
+        DartCObjectType::DartArray => {
+            if let Ok(items) = from_dart::array(obj) {
+                items.for_each(|item| collect_finalizers(item, finalizers));
+            }
+        },
+        _ => {},
+    }
+}
+
+unsafe extern "C" fn post_cobject(
+    port: DartPort,
+    message: *mut DartCObject,
+) -> bool {
+    let Some(message) = message.as_ref() else {
+        return false;
+    };
+    let native_handler = Vm::lock().native_ports.get(&port).copied();
+    if let Some(handler) = native_handler {
+        let mut finalizers = Vec::new();
+        if decode(message, &mut finalizers).is_err() {
+            return false;
+        }
+        // the message only lives as long as the handler runs.
+        handler(port, message as *const DartCObject as *mut DartCObject);
+        finalizers.into_iter().for_each(Finalizer::run);
+        return true;
+    }
+
+    let mut vm = Vm::lock();
+    let Some(port) = vm.ports.get_mut(&port) else {
+        return false;
+    };
+    let mut finalizers = Vec::new();
+    match decode(message, &mut finalizers) {
+        Ok(message) => {
+            port.messages.push_back((message, finalizers));
+            true
+        },
+        // the VM rejects messages it can not handle, and does not take
+        // ownership of anything in them.
+        Err(_) => false,
+    }
+}
+
+unsafe extern "C" fn post_integer(port: DartPort, message: i64) -> bool {
+    post_cobject(port, &mut message.into_dart())
+}
+
+unsafe extern "C" fn new_native_port(
+    _name: *const c_char,
+    handler: DartNativeMessageHandler,
+    _handle_concurrently: bool,
+) -> DartPort {
+    let mut vm = Vm::lock();
+    let port = vm.next_port();
+    vm.native_ports.insert(port, handler);
+    port
+}
+
+unsafe extern "C" fn close_native_port(port: DartPort) -> bool {
+    Vm::lock().native_ports.remove(&port).is_some()
+}
@@ -0,0 +1,175 @@
+//! Element types of typed data that have no Rust primitive counterpart.
+
+use std::ops::{Deref, DerefMut};
+
+use crate::{
+    ffi::*,
+    from_dart::{fixed, typed_data, typed_data_of},
+    into_dart::vec_to_dart_native_external_typed_data_of,
+    FromDart, FromDartError, FromDartExceptPrimitive, IntoDart,
+    IntoDartExceptPrimitive,
+};
+
+/// The lanes of a Dart `Float32x4`.
+///
+/// A `Vec<Float32x4>` is sent as a `Float32x4List`, copied or through a
+/// [`ZeroCopyBuffer`].
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Float32x4(pub [f32; 4]);
+
+/// The lanes of a Dart `Int32x4`.
+///
+/// A `Vec<Int32x4>` is sent as an `Int32x4List`, copied or through a
+/// [`ZeroCopyBuffer`].
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Int32x4(pub [i32; 4]);
+
+/// The lanes of a Dart `Float64x2`.
+///
+/// A `Vec<Float64x2>` is sent as a `Float64x2List`, copied or through a
+/// [`ZeroCopyBuffer`].
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Float64x2(pub [f64; 2]);
+
+macro_rules! lanes {
+    ($($simd:ident([$lane:ty; $n:literal]))+) => {$(
+        impl From<[$lane; $n]> for $simd {
+            fn from(lanes: [$lane; $n]) -> Self {
+                Self(lanes)
+            }
+        }
+
+        impl From<$simd> for [$lane; $n] {
+            fn from(simd: $simd) -> Self {
+                simd.0
+            }
+        }
+
+        /// Decoded from typed data (external or not) of the same type.
+        impl FromDart for Vec<$simd> {
+            fn from_dart(obj: &DartCObject) -> Result<Self, FromDartError> {
+                typed_data::<$simd>(obj).map(<[_]>::to_vec)
+            }
+        }
+
+        impl FromDartExceptPrimitive for Vec<$simd> {}
+
+        impl<const N: usize> FromDart for [$simd; N] {
+            fn from_dart(obj: &DartCObject) -> Result<Self, FromDartError> {
+                fixed(Vec::from_dart(obj)?)
+            }
+        }
+
+        impl FromDart for ZeroCopyBuffer<Vec<$simd>> {
+            fn from_dart(obj: &DartCObject) -> Result<Self, FromDartError> {
+                Vec::from_dart(obj).map(ZeroCopyBuffer)
+            }
+        }
+
+        impl<const N: usize> FromDart for ZeroCopyBuffer<[$simd; N]> {
+            fn from_dart(obj: &DartCObject) -> Result<Self, FromDartError> {
+                <[$simd; N]>::from_dart(obj).map(ZeroCopyBuffer)
+            }
+        }
+    )+};
+}
+
+lanes! {
+    Float32x4([f32; 4])
+    Int32x4([i32; 4])
+    Float64x2([f64; 2])
+}
+
+/// Bytes that are sent as a `Uint8ClampedList`, the pixel format of image
+/// APIs, instead of a `Uint8List`.
+///
+/// Wrap it in a [`ZeroCopyBuffer`] to send it without copying.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct Uint8Clamped(pub Vec<u8>);
+
+/// Bytes that are sent as `ByteData`, to be read with the accessors of
+/// `ByteData` on the Dart side, instead of a `Uint8List`.
+///
+/// Wrap it in a [`ZeroCopyBuffer`] to send it without copying.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct ByteData(pub Vec<u8>);
+
+macro_rules! bytes {
+    ($($bytes:ident)+) => {$(
+        impl From<Vec<u8>> for $bytes {
+            fn from(bytes: Vec<u8>) -> Self {
+                Self(bytes)
+            }
+        }
+
+        impl From<$bytes> for Vec<u8> {
+            fn from(bytes: $bytes) -> Self {
+                bytes.0
+            }
+        }
+
+        impl Deref for $bytes {
+            type Target = Vec<u8>;
+
+            fn deref(&self) -> &Vec<u8> {
+                &self.0
+            }
+        }
+
+        impl DerefMut for $bytes {
+            fn deref_mut(&mut self) -> &mut Vec<u8> {
+                &mut self.0
+            }
+        }
+
+        #[cfg(not(feature = "zero-copy"))]
+        impl IntoDart for $bytes {
+            fn into_dart(self) -> DartCObject {
+                crate::into_dart::vec_to_dart_native_typed_data(
+                    self.0,
+                    DartTypedDataType::$bytes,
+                )
+            }
+        }
+
+        #[cfg(feature = "zero-copy")]
+        impl IntoDart for $bytes {
+            fn into_dart(self) -> DartCObject {
+                ZeroCopyBuffer(self).into_dart()
+            }
+        }
+
+        impl IntoDartExceptPrimitive for $bytes {}
+
+        impl IntoDart for ZeroCopyBuffer<$bytes> {
+            fn into_dart(self) -> DartCObject {
+                vec_to_dart_native_external_typed_data_of(
+                    self.0 .0,
+                    DartTypedDataType::$bytes,
+                )
+            }
+        }
+
+        impl IntoDartExceptPrimitive for ZeroCopyBuffer<$bytes> {}
+
+        impl FromDart for $bytes {
+            fn from_dart(obj: &DartCObject) -> Result<Self, FromDartError> {
+                typed_data_of(obj, DartTypedDataType::$bytes)
+                    .map(|bytes| Self(bytes.to_vec()))
+            }
+        }
+
+        impl FromDartExceptPrimitive for $bytes {}
+
+        impl FromDart for ZeroCopyBuffer<$bytes> {
+            fn from_dart(obj: &DartCObject) -> Result<Self, FromDartError> {
+                $bytes::from_dart(obj).map(ZeroCopyBuffer)
+            }
+        }
+    )+};
+}
+
+bytes!(Uint8Clamped ByteData);
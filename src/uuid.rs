@@ -2,8 +2,9 @@
 
 use crate::{
     ffi::{DartCObject, DartHandleFinalizer, DartTypedDataType},
+    from_dart::{fixed, typed_data, FromDartError},
     into_dart::{free_zero_copy_buffer_u8, DartTypedDataTypeTrait},
-    IntoDart,
+    FromDart, IntoDart,
 };
 
 impl IntoDart for uuid::Uuid {
@@ -72,3 +73,41 @@ impl DartTypedDataTypeTrait for uuid::Uuid {
         free_zero_copy_buffer_u8
     }
 }
+
+impl FromDart for uuid::Uuid {
+    /// decoded from the 16 bytes sent by the `IntoDart` implementation
+    fn from_dart(obj: &DartCObject) -> Result<Self, FromDartError> {
+        let bytes = typed_data::<u8>(obj)?;
+        uuid::Uuid::from_slice(bytes).map_err(|_| {
+            FromDartError::LengthMismatch {
+                expected: 16,
+                found: bytes.len(),
+            }
+        })
+    }
+}
+
+impl FromDart for Vec<uuid::Uuid> {
+    /// decoded from the concatenated bytes sent by the `IntoDart`
+    /// implementation
+    fn from_dart(obj: &DartCObject) -> Result<Self, FromDartError> {
+        let bytes = typed_data::<u8>(obj)?;
+        if bytes.len() % 16 != 0 {
+            return Err(FromDartError::LengthMismatch {
+                expected: bytes.len() / 16 * 16 + 16,
+                found: bytes.len(),
+            });
+        }
+        Ok(bytes
+            // the length is a multiple of 16, checked above.
+            .chunks(16)
+            .map(|id| uuid::Uuid::from_bytes(id.try_into().expect("16 bytes")))
+            .collect())
+    }
+}
+
+impl<const N: usize> FromDart for [uuid::Uuid; N] {
+    fn from_dart(obj: &DartCObject) -> Result<Self, FromDartError> {
+        fixed(Vec::from_dart(obj)?)
+    }
+}
@@ -0,0 +1,387 @@
+//! An owned and safe mirror of [`DartCObject`].
+
+use std::{ffi::c_void, fmt};
+
+use crate::{
+    ffi::{
+        DartCObject, DartCObjectType, DartCObjectValue, DartNativePointer,
+        DartPort, DartTypedDataType,
+    },
+    from_dart, ByteData, Capability, Float32x4, Float64x2, FromDart,
+    FromDartError, FromDartExceptPrimitive, Int32x4, IntoDart,
+    IntoDartExceptPrimitive, SendPort, Uint8Clamped,
+};
+
+/// An owned Dart object, every [`DartCObject`] that can be sent or received
+/// has a `DartValue` counterpart.
+///
+/// It can be decoded from a received message with [`FromDart`], or built
+/// safely and sent with [`IntoDart`]. `Display` prints it the way Dart would
+/// print the object, and the alternate form (`{:#}`) puts every item of a
+/// `List` on its own line.
+///
+/// #### Example
+/// ```
+/// use allo_isolate::{DartValue, IntoDart};
+///
+/// let value = DartValue::List(vec![
+///     DartValue::from("frame"),
+///     DartValue::from(42),
+///     DartValue::from(vec![1u8, 2, 3]),
+/// ]);
+/// assert_eq!(value.to_string(), r#"["frame", 42, Uint8List[1, 2, 3]]"#);
+/// let obj = value.into_dart();
+/// # unsafe { allo_isolate::ffi::run_destructors(&obj) };
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub enum DartValue {
+    /// `null`
+    Null,
+    /// A `bool`.
+    Bool(bool),
+    /// An `int`, no matter if it was sent as `int32` or `int64`.
+    Int(i64),
+    /// A `double`.
+    Double(f64),
+    /// A `String`.
+    String(String),
+    /// A `List`.
+    List(Vec<DartValue>),
+    /// Typed data, no matter if it was copied or external.
+    TypedData(TypedData),
+    /// A `SendPort`.
+    SendPort {
+        /// The port messages are sent to.
+        id: DartPort,
+        /// The port of the isolate that created the `SendPort`.
+        origin_id: DartPort,
+    },
+    /// A `Capability`.
+    Capability(i64),
+    /// A pointer to native memory.
+    ///
+    /// Only the address is kept, sending it again does not hand any
+    /// finalizer to Dart.
+    NativePointer {
+        /// The address.
+        ptr: isize,
+        /// The size of the memory, for Dart's GC to account for.
+        size: isize,
+    },
+}
+
+macro_rules! typed_data {
+    ($($variant:ident($elements:ty) => $list:literal,)+) => {
+        /// The elements of typed data, for each [`DartTypedDataType`].
+        #[derive(Debug, Clone, PartialEq)]
+        #[non_exhaustive]
+        pub enum TypedData {
+            $(
+                #[doc = concat!("A `", $list, "`.")]
+                $variant($elements),
+            )+
+        }
+
+        impl TypedData {
+            /// The element type.
+            pub const fn ty(&self) -> DartTypedDataType {
+                match self {
+                    $(Self::$variant(_) => DartTypedDataType::$variant,)+
+                }
+            }
+
+            /// The number of elements.
+            pub fn len(&self) -> usize {
+                match self {
+                    $(Self::$variant(v) => v.len(),)+
+                }
+            }
+
+            /// Whether there are no elements.
+            pub fn is_empty(&self) -> bool {
+                self.len() == 0
+            }
+
+            fn from_dart(
+                obj: &DartCObject,
+                ty: DartTypedDataType,
+            ) -> Result<Self, FromDartError> {
+                match ty {
+                    $(
+                        DartTypedDataType::$variant => {
+                            <$elements>::from_dart(obj).map(Self::$variant)
+                        },
+                    )+
+                    _ => Err(FromDartError::UnexpectedType {
+                        expected: "supported typed data",
+                        found: obj.ty,
+                    }),
+                }
+            }
+        }
+
+        impl IntoDart for TypedData {
+            fn into_dart(self) -> DartCObject {
+                match self {
+                    $(Self::$variant(v) => v.into_dart(),)+
+                }
+            }
+        }
+
+        impl fmt::Display for TypedData {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                match self {
+                    $(Self::$variant(v) => {
+                        write!(f, "{}[", $list)?;
+                        for (i, e) in v.iter().enumerate() {
+                            if i > 0 {
+                                f.write_str(", ")?;
+                            }
+                            write!(f, "{:?}", e)?;
+                        }
+                        f.write_str("]")
+                    },)+
+                }
+            }
+        }
+
+        $(
+            impl From<$elements> for TypedData {
+                fn from(v: $elements) -> Self {
+                    Self::$variant(v)
+                }
+            }
+
+            impl From<$elements> for DartValue {
+                fn from(v: $elements) -> Self {
+                    Self::TypedData(TypedData::$variant(v))
+                }
+            }
+        )+
+    };
+}
+
+typed_data! {
+    ByteData(ByteData) => "ByteData",
+    Int8(Vec<i8>) => "Int8List",
+    Uint8(Vec<u8>) => "Uint8List",
+    Uint8Clamped(Uint8Clamped) => "Uint8ClampedList",
+    Int16(Vec<i16>) => "Int16List",
+    Uint16(Vec<u16>) => "Uint16List",
+    Int32(Vec<i32>) => "Int32List",
+    Uint32(Vec<u32>) => "Uint32List",
+    Int64(Vec<i64>) => "Int64List",
+    Uint64(Vec<u64>) => "Uint64List",
+    Float32(Vec<f32>) => "Float32List",
+    Float64(Vec<f64>) => "Float64List",
+    Int32x4(Vec<Int32x4>) => "Int32x4List",
+    Float32x4(Vec<Float32x4>) => "Float32x4List",
+    Float64x2(Vec<Float64x2>) => "Float64x2List",
+}
+
+impl IntoDartExceptPrimitive for TypedData {}
+
+impl FromDart for DartValue {
+    fn from_dart(obj: &DartCObject) -> Result<Self, FromDartError> {
+        use DartCObjectType::*;
+        let value = match obj.ty {
+            DartNull => Self::Null,
+            DartBool => Self::Bool(unsafe { obj.value.as_bool }),
+            DartInt32 | DartInt64 => Self::Int(from_dart::int(obj)?),
+            DartDouble => Self::Double(unsafe { obj.value.as_double }),
+            DartString => Self::String(from_dart::str(obj)?.to_owned()),
+            DartArray => Self::List(
+                from_dart::array(obj)?
+                    .map(Self::from_dart)
+                    .collect::<Result<_, _>>()?,
+            ),
+            DartTypedData => {
+                Self::TypedData(TypedData::from_dart(obj, unsafe {
+                    obj.value.as_typed_data.ty
+                })?)
+            },
+            DartExternalTypedData | DartUnmodifiableExternalTypedData => {
+                Self::TypedData(TypedData::from_dart(obj, unsafe {
+                    obj.value.as_external_typed_data.ty
+                })?)
+            },
+            DartSendPort => SendPort::from_dart(obj)?.into(),
+            DartCapability => Capability::from_dart(obj)?.into(),
+            DartNativePointer => {
+                let pointer = unsafe { obj.value.as_native_pointer };
+                Self::NativePointer {
+                    ptr: pointer.ptr,
+                    size: pointer.size,
+                }
+            },
+            DartUnsupported | DartNumberOfTypes => {
+                return Err(FromDartError::UnexpectedType {
+                    expected: "a supported object",
+                    found: obj.ty,
+                })
+            },
+        };
+        Ok(value)
+    }
+}
+
+impl FromDartExceptPrimitive for DartValue {}
+
+const unsafe extern "C" fn keep_native_pointer(
+    _isolate_callback_data: *mut c_void,
+    _peer: *mut c_void,
+) {
+}
+
+impl IntoDart for DartValue {
+    fn into_dart(self) -> DartCObject {
+        match self {
+            Self::Null => ().into_dart(),
+            Self::Bool(v) => v.into_dart(),
+            Self::Int(v) => v.into_dart(),
+            Self::Double(v) => v.into_dart(),
+            Self::String(v) => v.into_dart(),
+            Self::List(v) => v.into_dart(),
+            Self::TypedData(v) => v.into_dart(),
+            Self::SendPort { id, origin_id } => {
+                SendPort { id, origin_id }.into_dart()
+            },
+            Self::Capability(id) => Capability { id }.into_dart(),
+            Self::NativePointer { ptr, size } => DartCObject {
+                ty: DartCObjectType::DartNativePointer,
+                value: DartCObjectValue {
+                    as_native_pointer: DartNativePointer {
+                        ptr,
+                        size,
+                        callback: keep_native_pointer,
+                    },
+                },
+            },
+        }
+    }
+}
+
+impl IntoDartExceptPrimitive for DartValue {}
+
+impl DartValue {
+    fn write(&self, f: &mut fmt::Formatter<'_>, depth: usize) -> fmt::Result {
+        match self {
+            Self::Null => f.write_str("null"),
+            Self::Bool(v) => write!(f, "{}", v),
+            Self::Int(v) => write!(f, "{}", v),
+            Self::Double(v) => write!(f, "{:?}", v),
+            Self::String(v) => write!(f, "{:?}", v),
+            Self::List(items) if items.is_empty() => f.write_str("[]"),
+            Self::List(items) if f.alternate() => {
+                f.write_str("[\n")?;
+                for item in items {
+                    write!(f, "{:1$}", "", (depth + 1) * 2)?;
+                    item.write(f, depth + 1)?;
+                    f.write_str(",\n")?;
+                }
+                write!(f, "{:1$}]", "", depth * 2)
+            },
+            Self::List(items) => {
+                f.write_str("[")?;
+                for (i, item) in items.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(", ")?;
+                    }
+                    item.write(f, depth)?;
+                }
+                f.write_str("]")
+            },
+            // the elements are always printed on one line.
+            Self::TypedData(v) => write!(f, "{}", v),
+            Self::SendPort { id, origin_id } => {
+                write!(f, "SendPort(id: {}, origin: {})", id, origin_id)
+            },
+            Self::Capability(id) => write!(f, "Capability({})", id),
+            Self::NativePointer { ptr, size } => {
+                write!(f, "Pointer({:#x}, size: {})", ptr, size)
+            },
+        }
+    }
+}
+
+impl fmt::Display for DartValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.write(f, 0)
+    }
+}
+
+impl From<()> for DartValue {
+    fn from(_: ()) -> Self {
+        Self::Null
+    }
+}
+
+impl From<bool> for DartValue {
+    fn from(v: bool) -> Self {
+        Self::Bool(v)
+    }
+}
+
+impl From<i32> for DartValue {
+    fn from(v: i32) -> Self {
+        Self::Int(v.into())
+    }
+}
+
+impl From<i64> for DartValue {
+    fn from(v: i64) -> Self {
+        Self::Int(v)
+    }
+}
+
+impl From<f64> for DartValue {
+    fn from(v: f64) -> Self {
+        Self::Double(v)
+    }
+}
+
+impl From<String> for DartValue {
+    fn from(v: String) -> Self {
+        Self::String(v)
+    }
+}
+
+impl From<&str> for DartValue {
+    fn from(v: &str) -> Self {
+        Self::String(v.to_owned())
+    }
+}
+
+impl From<Vec<DartValue>> for DartValue {
+    fn from(v: Vec<DartValue>) -> Self {
+        Self::List(v)
+    }
+}
+
+impl From<SendPort> for DartValue {
+    fn from(v: SendPort) -> Self {
+        Self::SendPort {
+            id: v.id,
+            origin_id: v.origin_id,
+        }
+    }
+}
+
+impl From<Capability> for DartValue {
+    fn from(v: Capability) -> Self {
+        Self::Capability(v.id)
+    }
+}
+
+impl From<TypedData> for DartValue {
+    fn from(v: TypedData) -> Self {
+        Self::TypedData(v)
+    }
+}
+
+impl<T: Into<DartValue>> From<Option<T>> for DartValue {
+    fn from(v: Option<T>) -> Self {
+        v.map_or(Self::Null, Into::into)
+    }
+}
@@ -0,0 +1,94 @@
+use allo_isolate::{
+    dart_api_dl,
+    ffi::{DartApi, DartApiEntry, DartCObject, DartPort},
+    initialize_api_dl, store_dart_api_dl, ApiDlError, Isolate,
+};
+use std::{
+    ffi::{c_void, CStr},
+    ptr,
+    sync::atomic::{AtomicI64, Ordering},
+};
+
+static LAST_PORT: AtomicI64 = AtomicI64::new(0);
+
+unsafe extern "C" fn post_cobject(port: DartPort, _: *mut DartCObject) -> bool {
+    LAST_PORT.store(port, Ordering::SeqCst);
+    true
+}
+
+unsafe extern "C" fn post_integer(port: DartPort, _: i64) -> bool {
+    LAST_PORT.store(-port, Ordering::SeqCst);
+    true
+}
+
+fn entry(name: &'static CStr, function: *const ()) -> DartApiEntry {
+    DartApiEntry {
+        name: name.as_ptr(),
+        function: Some(unsafe {
+            std::mem::transmute::<*const (), unsafe extern "C" fn()>(function)
+        }),
+    }
+}
+
+const END: DartApiEntry = DartApiEntry {
+    name: ptr::null(),
+    function: None,
+};
+
+#[test]
+fn initialize() {
+    unsafe {
+        assert_eq!(
+            initialize_api_dl(ptr::null_mut()).unwrap_err(),
+            ApiDlError::NullData
+        );
+
+        let entries = [END];
+        let mut api = DartApi {
+            major: 1,
+            minor: 3,
+            functions: entries.as_ptr(),
+        };
+        let error =
+            initialize_api_dl(ptr::addr_of_mut!(api).cast()).unwrap_err();
+        assert_eq!(
+            error,
+            ApiDlError::IncompatibleVersion { major: 1, minor: 3 }
+        );
+        assert_eq!(
+            error.to_string(),
+            "the Dart VM provides version 1.3 of the Dart API DL, this crate \
+             needs version 2.x"
+        );
+
+        api.major = 2;
+        assert_eq!(
+            initialize_api_dl(ptr::addr_of_mut!(api).cast()).unwrap_err(),
+            ApiDlError::MissingFunction("Dart_PostCObject")
+        );
+        assert!(dart_api_dl().is_none());
+
+        let entries = [
+            entry(c"Dart_PostInteger", post_integer as *const ()),
+            entry(c"Dart_SomethingNew", post_integer as *const ()),
+            entry(c"Dart_PostCObject", post_cobject as *const ()),
+            END,
+        ];
+        api.functions = entries.as_ptr();
+        let data: *mut c_void = ptr::addr_of_mut!(api).cast();
+        assert_eq!(store_dart_api_dl(data), 0);
+    }
+
+    let api_dl = dart_api_dl().unwrap();
+    assert_eq!((api_dl.major, api_dl.minor), (2, 3));
+    assert!(api_dl.post_cobject.is_some());
+    assert!(api_dl.post_integer.is_some());
+    // left out by an older VM.
+    assert!(api_dl.new_native_port.is_none());
+    assert!(api_dl.new_persistent_handle.is_none());
+
+    assert!(Isolate::new(42).post("hello"));
+    assert_eq!(LAST_PORT.load(Ordering::SeqCst), 42);
+    assert!(Isolate::new(42).post_integer(1));
+    assert_eq!(LAST_PORT.load(Ordering::SeqCst), -42);
+}
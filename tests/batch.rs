@@ -0,0 +1,86 @@
+use allo_isolate::{
+    testing::{self, ReceivePort},
+    BatchingIsolate, DartValue, ZeroCopyBuffer,
+};
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+#[test]
+fn batches_messages() {
+    testing::install();
+    let port = ReceivePort::new();
+    let mut batch = port.isolate().batching().with_max_len(3);
+    for i in 0..5 {
+        assert!(batch.post(i));
+    }
+    assert_eq!(batch.len(), 2);
+    assert_eq!(
+        port.messages(),
+        vec![DartValue::List(vec![0.into(), 1.into(), 2.into()])]
+    );
+
+    assert!(batch.flush());
+    assert!(batch.is_empty());
+    assert!(batch.flush());
+    assert_eq!(
+        port.messages(),
+        vec![DartValue::List(vec![3.into(), 4.into()])]
+    );
+
+    batch.post("last");
+    drop(batch);
+    assert_eq!(port.recv(), Some(DartValue::List(vec!["last".into()])));
+    assert_eq!(port.recv(), None);
+}
+
+#[test]
+fn flushes_old_batches() {
+    testing::install();
+    let port = ReceivePort::new();
+    let mut batch = BatchingIsolate::new(port.isolate())
+        .with_max_delay(Duration::from_millis(20));
+    batch.post(1);
+    assert_eq!(port.recv(), None);
+    std::thread::sleep(Duration::from_millis(25));
+    // posted from another thread.
+    std::thread::spawn(move || batch.post(2)).join().unwrap();
+    assert_eq!(port.recv(), Some(DartValue::List(vec![1.into(), 2.into()])));
+}
+
+#[test]
+fn flushes_on_deadline() {
+    testing::install();
+    let port = ReceivePort::new();
+    let mut batch = BatchingIsolate::new(port.isolate())
+        .with_max_delay(Duration::from_millis(50));
+    assert_eq!(batch.deadline(), None);
+    assert!(batch.poll_flush());
+    let before = Instant::now();
+    batch.post(1);
+    let deadline = batch.deadline().unwrap();
+    assert!(deadline >= before + Duration::from_millis(50));
+
+    // the last message of a burst is not left waiting.
+    assert!(batch.poll_flush());
+    assert_eq!(port.recv(), None);
+    std::thread::sleep(deadline - Instant::now());
+    assert!(batch.poll_flush());
+    assert_eq!(port.recv(), Some(DartValue::List(vec![1.into()])));
+    assert_eq!(batch.deadline(), None);
+}
+
+#[test]
+fn closed_port() {
+    testing::install();
+    let port = ReceivePort::new();
+    let frame: Arc<[u8]> = Arc::from(vec![1u8; 8]);
+    let mut batch = port.isolate().batching();
+    batch.post(ZeroCopyBuffer(frame.clone()));
+    assert_eq!(Arc::strong_count(&frame), 2);
+    port.close();
+    // the batch is lost, and what it held is released.
+    assert!(!batch.flush());
+    assert_eq!(Arc::strong_count(&frame), 1);
+}
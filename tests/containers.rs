@@ -1,14 +1,15 @@
-use allo_isolate::{ffi::DartCObjectType, IntoDart, Isolate, ZeroCopyBuffer};
+use allo_isolate::{
+    ffi::DartCObjectType,
+    testing::{self, ReceivePort},
+    IntoDart, Isolate, PostError, ZeroCopyBuffer,
+};
 use std::collections::{HashMap, HashSet};
 
-mod vm;
-
 fn main() {
     // Create a Dart VM call before we could handle sending a message back to
     // Dart
-    let port = vm::port();
-    assert_ne!(port, -1);
-    let isolate = Isolate::new(port);
+    let port = ReceivePort::new();
+    let isolate = port.isolate();
     assert!(!isolate.post(vec![String::from("Rust"); 8]));
     assert!(!isolate.post(vec![String::from("Dart"); 1024]));
     assert!(!isolate.post(vec![42i8; 100]));
@@ -66,16 +67,17 @@ fn main() {
     assert!(!isolate.post(ZeroCopyBuffer([42usize; 100])));
     assert!(!isolate.post(ZeroCopyBuffer([42.0f32; 100])));
     assert!(!isolate.post(ZeroCopyBuffer([42.0f64; 100])));
+    assert_eq!(
+        isolate.try_post(ZeroCopyBuffer(vec![42u8; 100])),
+        Err(PostError::NotInitialized)
+    );
     // Provide the pointer that allows Rust to communicate messages back to the
     // Dart VM
-    unsafe {
-        allo_isolate::store_dart_post_cobject(vm::dart_post_cobject);
-    }
+    testing::install();
 
     // Post some messages that will succeed
-    let port = vm::port();
-    assert_ne!(port, -1);
-    let isolate = Isolate::new(port);
+    let port = ReceivePort::new();
+    let isolate = port.isolate();
     assert!(isolate.post(42i8));
     assert!(isolate.post(42u8));
     assert!(isolate.post(42i16));
@@ -93,20 +95,27 @@ fn main() {
     assert!(isolate.post(true));
     assert!(isolate.post(false));
     assert!(isolate.post('🎊'));
+    assert_eq!(isolate.try_post(42i32), Ok(()));
+
+    // A port the VM does not know about is reported as closed
+    let closed = Isolate::new(0);
+    assert_eq!(closed.try_post(42i32), Err(PostError::PortClosed));
+    assert_eq!(
+        closed.try_post(vec![ZeroCopyBuffer(vec![42u8; 100])]),
+        Err(PostError::PortClosed)
+    );
 
     // Create another isolate and port that still works
-    let port = vm::port();
-    assert_ne!(port, -1);
-    let isolate = Isolate::new(port);
+    let port = ReceivePort::new();
+    let isolate = port.isolate();
 
     assert!(isolate.post(String::new()));
     assert!(isolate.post(String::from("Hello Dart")));
     assert!(isolate.post("Hello Dart"));
 
     // Create another isolate and port that still works
-    let port = vm::port();
-    assert_ne!(port, -1);
-    let isolate2 = Isolate::new(port);
+    let port2 = ReceivePort::new();
+    let isolate2 = port2.isolate();
 
     // Send data to the new port
     assert!(isolate2.post(String::new()));
@@ -127,9 +136,8 @@ fn main() {
     assert!(isolate.post(ZeroCopyBuffer(vec![42.0f64; 100])));
 
     // Create another port and send all the data successfully
-    let port = vm::port();
-    assert_ne!(port, -1);
-    let isolate = Isolate::new(port);
+    let port = ReceivePort::new();
+    let isolate = port.isolate();
 
     assert!(isolate.post(vec![String::from("Rust"); 8]));
     assert!(isolate.post(vec![String::from("Dart"); 1024]));
@@ -0,0 +1,171 @@
+use allo_isolate::{
+    ffi::{run_destructors, DartCObjectType},
+    testing::{self, ReceivePort},
+    DartValue, ExternalBuffer, ExternalTypedData, IntoDart, TypedData,
+    ZeroCopyBuffer,
+};
+use std::sync::Arc;
+
+#[test]
+fn shared_buffers() {
+    testing::install();
+    let port = ReceivePort::new();
+    let isolate = port.isolate();
+    let frame: Arc<[u8]> = Arc::from(vec![7u8; 64]);
+    assert!(isolate.post(ZeroCopyBuffer(frame.clone())));
+    assert!(isolate.post(ZeroCopyBuffer(frame.clone())));
+    // Dart holds on to the buffer until it is finalized.
+    assert_eq!(Arc::strong_count(&frame), 3);
+
+    assert!(isolate.post(ZeroCopyBuffer(vec![0.5f64; 4].into_boxed_slice())));
+    assert!(isolate.post(ZeroCopyBuffer(Box::<[i16]>::from([]))));
+    assert_eq!(
+        port.messages(),
+        vec![
+            DartValue::from(vec![7u8; 64]),
+            DartValue::from(vec![7u8; 64]),
+            DartValue::from(vec![0.5f64; 4]),
+            DartValue::TypedData(TypedData::Int16(vec![])),
+        ]
+    );
+    testing::gc();
+    assert_eq!(Arc::strong_count(&frame), 1);
+
+    // Dart must not write to memory that Rust still reads.
+    let obj = ZeroCopyBuffer(frame.clone()).into_dart();
+    assert_eq!(obj.ty, DartCObjectType::DartUnmodifiableExternalTypedData);
+    unsafe { run_destructors(&obj) };
+    let obj = ZeroCopyBuffer(Box::<[u8]>::from([1])).into_dart();
+    assert_eq!(obj.ty, DartCObjectType::DartExternalTypedData);
+    unsafe { run_destructors(&obj) };
+    assert_eq!(Arc::strong_count(&frame), 1);
+
+    // nothing is kept when the port is closed.
+    port.close();
+    assert!(!isolate.post(ZeroCopyBuffer(frame.clone())));
+    assert_eq!(Arc::strong_count(&frame), 1);
+}
+
+#[cfg(feature = "bytes")]
+#[test]
+fn bytes() {
+    testing::install();
+    let port = ReceivePort::new();
+    let bytes = bytes::Bytes::from(vec![1u8, 2, 3, 4]);
+    assert!(port.isolate().post(ZeroCopyBuffer(bytes.slice(1..3))));
+    assert_eq!(port.recv(), Some(DartValue::from(vec![2u8, 3])));
+    testing::gc();
+    assert!(bytes.is_unique());
+
+    let obj = ZeroCopyBuffer(bytes.clone()).into_dart();
+    assert_eq!(obj.ty, DartCObjectType::DartUnmodifiableExternalTypedData);
+    unsafe { run_destructors(&obj) };
+    assert!(bytes.is_unique());
+}
+
+#[test]
+fn external_buffer() {
+    struct Asset(Vec<u8>);
+    impl AsRef<[u8]> for Asset {
+        fn as_ref(&self) -> &[u8] {
+            &self.0
+        }
+    }
+
+    testing::install();
+    let port = ReceivePort::new();
+    assert!(port
+        .isolate()
+        .post(ExternalBuffer::new(Asset(vec![1, 2, 3]))));
+    assert!(port.isolate().post(ExternalBuffer::new("text")));
+    assert_eq!(
+        port.messages(),
+        vec![
+            DartValue::from(vec![1u8, 2, 3]),
+            DartValue::from(b"text".to_vec())
+        ]
+    );
+    testing::gc();
+
+    // a `&'static str` is read-only memory.
+    let obj = ExternalBuffer::new("text").into_dart();
+    assert_eq!(obj.ty, DartCObjectType::DartUnmodifiableExternalTypedData);
+    unsafe { run_destructors(&obj) };
+}
+
+#[test]
+fn external_typed_data() {
+    struct Samples {
+        frames: Vec<[f32; 2]>,
+        _alive: Arc<()>,
+    }
+    impl AsRef<[f32]> for Samples {
+        fn as_ref(&self) -> &[f32] {
+            self.frames.as_flattened()
+        }
+    }
+
+    testing::install();
+    let port = ReceivePort::new();
+    let dropped = Arc::new(());
+    let samples = Samples {
+        frames: vec![[0.25, -0.25]; 3],
+        _alive: dropped.clone(),
+    };
+    assert!(port.isolate().post(ExternalTypedData::new(samples)));
+    assert_eq!(
+        port.recv(),
+        Some(DartValue::TypedData(TypedData::Float32(
+            [0.25, -0.25].repeat(3)
+        )))
+    );
+    assert_eq!(Arc::strong_count(&dropped), 2);
+    testing::gc();
+    assert_eq!(Arc::strong_count(&dropped), 1);
+
+    let owner = ExternalTypedData::<_, i64>::new(vec![1i64, 2]);
+    assert_eq!(owner.into_inner(), vec![1, 2]);
+}
+
+#[cfg(feature = "memmap2")]
+#[test]
+fn mmap() {
+    use std::{fs::File, io::Write};
+
+    let path = std::env::temp_dir()
+        .join(format!("allo-isolate-mmap-{}", std::process::id()));
+    File::create(&path).unwrap().write_all(&[42; 4096]).unwrap();
+    let map = || unsafe { memmap2::Mmap::map(&File::open(&path).unwrap()) };
+    let (first, second) = (map().unwrap(), map().unwrap());
+    std::fs::remove_file(&path).unwrap();
+
+    testing::install();
+    let port = ReceivePort::new();
+    assert!(port.isolate().post(ZeroCopyBuffer(first)));
+    assert_eq!(port.recv(), Some(DartValue::from(vec![42u8; 4096])));
+    assert!(testing::gc() >= 1);
+
+    // the mapping is read-only.
+    let obj = ZeroCopyBuffer(second).into_dart();
+    assert_eq!(obj.ty, DartCObjectType::DartUnmodifiableExternalTypedData);
+    unsafe { run_destructors(&obj) };
+}
+
+#[cfg(feature = "uuid")]
+#[test]
+fn uuids() {
+    testing::install();
+    let port = ReceivePort::new();
+    let ids = vec![uuid::Uuid::from_bytes([1; 16]), uuid::Uuid::nil()];
+    // the length of a `Uint8List` counts bytes, not `Uuid`s.
+    assert!(port.isolate().post(ExternalTypedData::new(ids.clone())));
+    assert!(port
+        .isolate()
+        .post(ZeroCopyBuffer(ids.clone().into_boxed_slice())));
+    let bytes = [[1u8; 16], [0; 16]].concat();
+    assert_eq!(
+        port.messages(),
+        vec![DartValue::from(bytes.clone()), DartValue::from(bytes)]
+    );
+    testing::gc();
+}
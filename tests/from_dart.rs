@@ -0,0 +1,150 @@
+use allo_isolate::{
+    ffi::{DartCObject, DartTypedDataType},
+    FromDart, FromDartError, IntoDart, ZeroCopyBuffer,
+};
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::Debug,
+};
+
+fn decode<T: FromDart>(obj: DartCObject) -> Result<T, FromDartError> {
+    let decoded = T::from_dart(&obj);
+    // release the external typed data, as the Dart VM would have done
+    unsafe { allo_isolate::ffi::run_destructors(&obj) };
+    decoded
+}
+
+fn roundtrip<T>(value: T)
+where
+    T: IntoDart + FromDart + Clone + PartialEq + Debug,
+{
+    assert_eq!(decode::<T>(value.clone().into_dart()), Ok(value));
+}
+
+#[test]
+fn primitives() {
+    roundtrip(());
+    roundtrip(true);
+    roundtrip(-42i8);
+    roundtrip(42u8);
+    roundtrip(-42i16);
+    roundtrip(42u16);
+    roundtrip(-42i32);
+    roundtrip(0xfe112233_u32);
+    roundtrip(-42i64);
+    roundtrip(u64::MAX);
+    roundtrip(-42isize);
+    roundtrip(usize::MAX);
+    roundtrip(i128::MIN);
+    roundtrip(u128::MAX);
+    roundtrip(4.5f32);
+    roundtrip(4.5f64);
+    roundtrip('🎊');
+    roundtrip(String::from("Hello Rust"));
+    roundtrip(Some(42i32));
+    roundtrip(None::<i32>);
+
+    assert_eq!(decode::<f64>(42i32.into_dart()), Ok(42.0));
+    assert_eq!(
+        decode::<u8>(300i32.into_dart()),
+        Err(FromDartError::OutOfRange)
+    );
+    assert!(matches!(
+        decode::<String>(42i32.into_dart()),
+        Err(FromDartError::UnexpectedType { .. })
+    ));
+}
+
+#[test]
+fn containers() {
+    roundtrip(vec![42i8; 10]);
+    roundtrip(vec![42u8; 10]);
+    roundtrip(vec![42i16; 10]);
+    roundtrip(vec![42u16; 10]);
+    roundtrip(vec![42i32; 10]);
+    roundtrip(vec![42u32; 10]);
+    roundtrip(vec![42i64; 10]);
+    roundtrip(vec![42u64; 10]);
+    roundtrip(vec![42isize; 10]);
+    roundtrip(vec![42usize; 10]);
+    roundtrip(vec![42.0f32; 10]);
+    roundtrip(vec![42.0f64; 10]);
+    roundtrip(Vec::<u8>::new());
+    roundtrip(vec![true, false]);
+    roundtrip([42u8; 4]);
+    roundtrip([[true, false], [false, true]]);
+    roundtrip(vec![String::from("Rust"); 8]);
+    roundtrip(vec![vec![String::from("Dart"); 2]; 2]);
+    roundtrip(HashSet::from([1u8, 2, 3]));
+    roundtrip(HashSet::from([String::from("value")]));
+    roundtrip(HashMap::from([(String::from("key"), vec![42u8])]));
+    roundtrip(("asd".to_string(), 123i32, (true,)));
+    roundtrip((1, 2, 3, 4, 5, 6, 7, 8, 9, (10, 11)));
+
+    let zero_copy = decode::<ZeroCopyBuffer<Vec<u16>>>(
+        ZeroCopyBuffer(vec![42u16; 10]).into_dart(),
+    );
+    assert_eq!(zero_copy.map(|buffer| buffer.0), Ok(vec![42u16; 10]));
+
+    // `List<int>` is accepted where typed data is expected
+    assert_eq!(
+        decode::<Vec<u8>>(vec![Some(1i32), Some(2)].into_dart()),
+        Ok(vec![1, 2])
+    );
+    assert_eq!(
+        decode::<Vec<u16>>(vec![1u8].into_dart()),
+        Err(FromDartError::UnexpectedTypedDataType {
+            expected: DartTypedDataType::Uint16,
+            found: DartTypedDataType::Uint8,
+        })
+    );
+    assert_eq!(
+        decode::<[u8; 2]>(vec![1u8].into_dart()),
+        Err(FromDartError::LengthMismatch {
+            expected: 2,
+            found: 1
+        })
+    );
+    assert_eq!(
+        decode::<(i32, i32)>((1,).into_dart()),
+        Err(FromDartError::LengthMismatch {
+            expected: 2,
+            found: 1
+        })
+    );
+}
+
+#[cfg(feature = "chrono")]
+#[test]
+fn chrono() {
+    let date = chrono::NaiveDate::from_ymd_opt(1776, 7, 4).unwrap();
+    let date_time = date.and_hms_micro_opt(9, 10, 11, 123_456).unwrap();
+    roundtrip(date);
+    roundtrip(date_time);
+    roundtrip(vec![date; 2]);
+    roundtrip([date_time; 2]);
+    roundtrip(chrono::Duration::hours(24));
+    roundtrip(vec![chrono::Duration::hours(24); 2]);
+    let utc = chrono::DateTime::<chrono::Utc>::from_naive_utc_and_offset(
+        date_time,
+        chrono::Utc,
+    );
+    roundtrip(utc);
+    roundtrip(vec![utc; 2]);
+}
+
+#[cfg(feature = "uuid")]
+#[test]
+fn uuid() {
+    let id = uuid::Uuid::new_v4();
+    roundtrip(id);
+    roundtrip(vec![id, uuid::Uuid::new_v4()]);
+    roundtrip([id; 2]);
+    assert_eq!(
+        decode::<uuid::Uuid>(vec![0u8; 4].into_dart()),
+        Err(FromDartError::LengthMismatch {
+            expected: 16,
+            found: 4
+        })
+    );
+}
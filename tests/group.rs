@@ -0,0 +1,87 @@
+use allo_isolate::{
+    testing::{self, ReceivePort},
+    DartValue, Isolate, IsolateGroup, ZeroCopyBuffer,
+};
+use std::sync::Arc;
+
+#[test]
+fn broadcast() {
+    testing::install();
+    let ui = ReceivePort::new();
+    let sync = ReceivePort::new();
+    let logs = ReceivePort::new();
+    let mut group: IsolateGroup =
+        [ui.isolate(), sync.isolate(), logs.isolate(), ui.isolate()]
+            .into_iter()
+            .collect();
+    assert_eq!(group.len(), 3);
+    assert!(!group.insert(sync.isolate()));
+
+    assert_eq!(group.broadcast(vec![("event", 1)]), 3);
+    for port in [&ui, &sync, &logs] {
+        assert_eq!(
+            port.recv(),
+            Some(DartValue::List(vec![DartValue::List(vec![
+                "event".into(),
+                1.into()
+            ])]))
+        );
+    }
+
+    // closed ports are pruned.
+    sync.close();
+    assert_eq!(group.broadcast("next"), 2);
+    assert!(!group.contains(sync.isolate()));
+    assert_eq!(
+        group.iter().collect::<Vec<_>>(),
+        vec![ui.isolate(), logs.isolate()]
+    );
+    assert_eq!(ui.recv(), Some("next".into()));
+    assert_eq!(logs.recv(), Some("next".into()));
+
+    assert!(group.remove(logs.isolate()));
+    assert!(!group.remove(Isolate::new(-1)));
+    assert_eq!(group.len(), 1);
+}
+
+#[test]
+fn broadcast_zero_copy() {
+    testing::install();
+    let a = ReceivePort::new();
+    let b = ReceivePort::new();
+    let closed = ReceivePort::new();
+    closed.close();
+    let mut group = IsolateGroup::new();
+    group.extend([a.isolate(), closed.isolate(), b.isolate()]);
+
+    let frame: Arc<[u8]> = Arc::from(vec![9u8; 4]);
+    // the last isolate gets the frame, the others a copy of it.
+    assert_eq!(group.broadcast(ZeroCopyBuffer(frame.clone())), 2);
+    assert_eq!(Arc::strong_count(&frame), 2);
+    assert_eq!(a.recv(), Some(vec![9u8; 4].into()));
+    assert_eq!(b.recv(), Some(vec![9u8; 4].into()));
+    testing::gc();
+    assert_eq!(Arc::strong_count(&frame), 1);
+
+    // released when the last isolate is closed.
+    b.close();
+    assert_eq!(group.broadcast(ZeroCopyBuffer(frame.clone())), 1);
+    assert_eq!(Arc::strong_count(&frame), 1);
+    assert_eq!(a.recv(), Some(vec![9u8; 4].into()));
+
+    assert_eq!(IsolateGroup::new().broadcast(1), 0);
+}
+
+#[test]
+fn broadcast_large_buffer() {
+    testing::install();
+    let ports = [ReceivePort::new(), ReceivePort::new(), ReceivePort::new()];
+    let mut group: IsolateGroup =
+        ports.iter().map(ReceivePort::isolate).collect();
+    // every isolate finalizes its own buffer, with or without `zero-copy`.
+    assert_eq!(group.broadcast(ZeroCopyBuffer(vec![7u8; 1 << 20])), 3);
+    for port in &ports {
+        assert_eq!(port.recv(), Some(vec![7u8; 1 << 20].into()));
+    }
+    assert!(testing::gc() >= 1);
+}
@@ -0,0 +1,111 @@
+use allo_isolate::{
+    testing::{self, ReceivePort},
+    DartValue, ExternalTypedData, ZeroCopyBuffer,
+};
+use std::sync::Arc;
+
+#[test]
+fn builds_nested_messages() {
+    testing::install();
+    let port = ReceivePort::new();
+    let rows: Vec<(String, i64)> =
+        (0..1000).map(|i| (format!("row {}", i), i)).collect();
+    assert!(port.isolate().post_with(|b| {
+        b.list(rows.iter().map(|(name, count)| {
+            b.list([
+                b.string(name),
+                b.int(*count),
+                b.list((0..*count % 3).map(|i| b.double(i as f64))),
+            ])
+        }))
+    }));
+    let expected = rows
+        .iter()
+        .map(|(name, count)| {
+            DartValue::List(vec![
+                name.as_str().into(),
+                (*count).into(),
+                DartValue::List(
+                    (0..*count % 3).map(|i| (i as f64).into()).collect(),
+                ),
+            ])
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(port.recv(), Some(DartValue::List(expected)));
+
+    assert!(port.isolate().post_with(|b| b.list([
+        b.null(),
+        b.bool(true),
+        b.string("nul\0"),
+        b.list([]),
+        b.value(vec![1u8, 2]),
+        b.value(Some("owned")),
+    ])));
+    assert_eq!(
+        port.recv(),
+        Some(DartValue::List(vec![
+            DartValue::Null,
+            true.into(),
+            "".into(),
+            DartValue::List(vec![]),
+            vec![1u8, 2].into(),
+            "owned".into(),
+        ]))
+    );
+}
+
+#[test]
+fn hands_over_external_typed_data() {
+    testing::install();
+    let port = ReceivePort::new();
+    let isolate = port.isolate();
+    let frame: Arc<[f32]> = Arc::from(vec![0.5; 16]);
+    assert!(isolate.post_with(|b| {
+        b.list([b.int(1), b.value(ZeroCopyBuffer(frame.clone()))])
+    }));
+    assert_eq!(Arc::strong_count(&frame), 2);
+    assert_eq!(
+        port.recv(),
+        Some(DartValue::List(vec![1.into(), vec![0.5f32; 16].into()]))
+    );
+    testing::gc();
+    assert_eq!(Arc::strong_count(&frame), 1);
+
+    // released right away when the port is closed.
+    port.close();
+    assert!(!isolate.post_with(|b| {
+        b.list([b.value(ExternalTypedData::new(frame.clone()))])
+    }));
+    assert_eq!(Arc::strong_count(&frame), 1);
+}
+
+#[test]
+fn releases_values_left_out() {
+    testing::install();
+    let port = ReceivePort::new();
+    let frame: Arc<[u8]> = Arc::from(vec![1; 8]);
+    assert!(port.isolate().post_with(|b| {
+        let _unused = b.value(ZeroCopyBuffer(frame.clone()));
+        let _unused_list =
+            b.list([b.value(vec![ZeroCopyBuffer(frame.clone())])]);
+        b.list([b.value(ZeroCopyBuffer(frame.clone()))])
+    }));
+    // only the buffer that is part of the message is handed over.
+    assert_eq!(Arc::strong_count(&frame), 2);
+    assert_eq!(
+        port.recv(),
+        Some(DartValue::List(vec![vec![1u8; 8].into()]))
+    );
+    testing::gc();
+    assert_eq!(Arc::strong_count(&frame), 1);
+
+    let panicked = std::panic::catch_unwind(|| {
+        port.isolate().post_with(|b| {
+            b.value(ZeroCopyBuffer(frame.clone()));
+            panic!("building the message failed")
+        })
+    });
+    assert!(panicked.is_err());
+    assert_eq!(Arc::strong_count(&frame), 1);
+    assert_eq!(port.recv(), None);
+}
@@ -0,0 +1,43 @@
+use allo_isolate::{testing, FromDart, NativePort, NativePortError};
+use std::sync::{Arc, Mutex};
+
+#[test]
+fn native_port() {
+    assert_eq!(
+        NativePort::new("too early", |_| {}).err(),
+        Some(NativePortError::NotInitialized)
+    );
+    testing::install();
+    assert_eq!(
+        NativePort::new("nul\0", |_| {}).err(),
+        Some(NativePortError::InvalidName)
+    );
+
+    let received = Arc::new(Mutex::new(Vec::new()));
+    let port = {
+        let received = received.clone();
+        NativePort::new("numbers", move |msg| {
+            received.lock().unwrap().push(i64::from_dart(msg).unwrap());
+        })
+        .unwrap()
+    };
+    let isolate = port.isolate();
+    assert!(isolate.post(1i64));
+    assert!(isolate.post(2i32));
+    assert_eq!(*received.lock().unwrap(), vec![1, 2]);
+
+    // a panicking handler does not bring everything down
+    let panicking = NativePort::new("panics", |_| panic!("boom")).unwrap();
+    assert!(panicking.isolate().post(3i64));
+    drop(panicking);
+
+    assert!(port.close());
+    assert!(!isolate.post(3i64));
+    assert_eq!(*received.lock().unwrap(), vec![1, 2]);
+
+    // dropping the port closes it too
+    let port = NativePort::new_concurrent("dropped", |_| {}).unwrap();
+    let isolate = port.isolate();
+    drop(port);
+    assert!(!isolate.post(4i64));
+}
@@ -0,0 +1,73 @@
+use allo_isolate::{
+    testing::{self, ReceivePort},
+    BufferPool, DartValue, PoolStats, ZeroCopyBuffer,
+};
+
+#[test]
+fn recycles_buffers() {
+    testing::install();
+    let port = ReceivePort::new();
+    let isolate = port.isolate();
+    let pool = BufferPool::new();
+
+    for frame in 0..3u8 {
+        let mut buffer = pool.get();
+        buffer.extend_from_slice(&[frame; 1024]);
+        assert!(isolate.post(ZeroCopyBuffer(buffer)));
+    }
+    assert_eq!(
+        pool.stats(),
+        PoolStats {
+            outstanding: 3,
+            recycled: 0,
+            allocated: 3,
+        }
+    );
+    assert_eq!(port.recv(), Some(DartValue::from(vec![0u8; 1024])));
+    assert_eq!(port.messages().len(), 2);
+    testing::gc();
+    assert_eq!(pool.stats().outstanding, 0);
+
+    // the buffers come back empty, with their capacity.
+    let buffer = pool.get();
+    assert!(buffer.is_empty());
+    assert!(buffer.capacity() >= 1024);
+    drop(buffer);
+    assert_eq!(
+        pool.stats(),
+        PoolStats {
+            outstanding: 0,
+            recycled: 1,
+            allocated: 3,
+        }
+    );
+
+    // a buffer that could not be posted goes back right away.
+    port.close();
+    let mut buffer = pool.get();
+    buffer.push(1u8);
+    assert!(!isolate.post(ZeroCopyBuffer(buffer)));
+    assert_eq!(pool.stats().outstanding, 0);
+}
+
+#[test]
+fn into_inner() {
+    let pool = BufferPool::<f32>::default();
+    let mut buffer = pool.clone().get();
+    buffer.push(0.5);
+    assert_eq!(buffer.into_inner(), vec![0.5]);
+    assert_eq!(
+        pool.stats(),
+        PoolStats {
+            outstanding: 0,
+            recycled: 0,
+            allocated: 1,
+        }
+    );
+    // it is not given back.
+    pool.get();
+    assert_eq!(pool.stats().allocated, 2);
+    pool.shrink();
+    pool.get();
+    assert_eq!(pool.stats().allocated, 3);
+}
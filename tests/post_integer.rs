@@ -0,0 +1,40 @@
+use allo_isolate::{
+    ffi::{DartCObject, DartCObjectType, DartPort},
+    store_dart_post_cobject, store_dart_post_integer, Isolate,
+};
+use std::sync::Mutex;
+
+static POSTED: Mutex<Vec<(&str, DartPort, i64)>> = Mutex::new(Vec::new());
+
+unsafe extern "C" fn post_cobject(
+    port: DartPort,
+    message: *mut DartCObject,
+) -> bool {
+    let message = &*message;
+    assert_eq!(message.ty, DartCObjectType::DartInt64);
+    POSTED
+        .lock()
+        .unwrap()
+        .push(("cobject", port, message.value.as_int64));
+    true
+}
+
+unsafe extern "C" fn post_integer(port: DartPort, message: i64) -> bool {
+    POSTED.lock().unwrap().push(("integer", port, message));
+    port != 0
+}
+
+#[test]
+fn falls_back_to_post() {
+    let isolate = Isolate::new(7);
+    unsafe { store_dart_post_cobject(post_cobject) };
+    assert!(isolate.post_integer(1));
+
+    unsafe { store_dart_post_integer(post_integer) };
+    assert!(isolate.post_integer(2));
+    assert!(!Isolate::new(0).post_integer(3));
+    assert_eq!(
+        *POSTED.lock().unwrap(),
+        vec![("cobject", 7, 1), ("integer", 7, 2), ("integer", 0, 3)]
+    );
+}
@@ -0,0 +1,177 @@
+#![cfg(feature = "rpc")]
+
+use allo_isolate::{
+    rpc::{RpcRegistry, RpcServer},
+    testing::{self, ReceivePort},
+    DartValue, IntoDart, Isolate, SendPort,
+};
+use futures::{
+    channel::oneshot,
+    executor::block_on,
+    future::{self, BoxFuture},
+};
+use std::{
+    sync::{mpsc, Arc, Mutex},
+    thread,
+    time::Duration,
+};
+
+/// Runs every request on its own thread.
+fn spawn(task: BoxFuture<'static, ()>) {
+    thread::spawn(move || block_on(task));
+}
+
+fn request(
+    server: &RpcServer,
+    id: i64,
+    method: i64,
+    reply: &ReceivePort,
+    payload: impl IntoDart,
+) {
+    let reply_port = SendPort::new(reply.port());
+    assert!(
+        Isolate::new(server.port()).post((0, id, method, reply_port, payload))
+    );
+}
+
+fn wait(port: &ReceivePort) -> DartValue {
+    for _ in 0..500 {
+        if let Some(msg) = port.recv() {
+            return msg;
+        }
+        thread::sleep(Duration::from_millis(2));
+    }
+    panic!("no reply");
+}
+
+fn reply(id: i64, tag: i64, payload: impl Into<DartValue>) -> DartValue {
+    DartValue::List(vec![id.into(), tag.into(), payload.into()])
+}
+
+#[test]
+fn replies() {
+    testing::install();
+    let server = RpcRegistry::new()
+        .register(1, |(a, b): (i64, i64)| async move {
+            a.checked_add(b).ok_or("overflow")
+        })
+        .register(2, |name: String| async move {
+            Ok::<_, String>(format!("hello {}", name))
+        })
+        .serve("rpc", spawn)
+        .unwrap();
+    let port = ReceivePort::new();
+
+    request(&server, 7, 1, &port, (2, 3));
+    assert_eq!(wait(&port), reply(7, 0, 5));
+    request(&server, 8, 1, &port, (i64::MAX, 1));
+    assert_eq!(wait(&port), reply(8, 1, "overflow"));
+    request(&server, 9, 2, &port, "dart");
+    assert_eq!(wait(&port), reply(9, 0, "hello dart"));
+
+    // the payload is not a string.
+    request(&server, 10, 2, &port, 1);
+    assert!(matches!(
+        wait(&port),
+        DartValue::List(items) if items[..2] == [10.into(), 1.into()]
+    ));
+    request(&server, 11, 3, &port, ());
+    assert_eq!(wait(&port), reply(11, 1, "unknown method 3"));
+
+    // not a request, ignored.
+    assert!(Isolate::new(server.port()).post((5, 1)));
+    assert!(Isolate::new(server.port()).post("hello"));
+    assert_eq!(port.recv(), None);
+
+    // the port is closed with the server.
+    let server_port = server.port();
+    drop(server);
+    let reply_port = SendPort::new(port.port());
+    assert!(!Isolate::new(server_port).post((0, 12, 1, reply_port, (1, 1))));
+}
+
+#[test]
+fn cancellation() {
+    testing::install();
+    let (started, is_started) = oneshot::channel::<()>();
+    let started = Arc::new(Mutex::new(Some(started)));
+    let (_never, never) = oneshot::channel::<()>();
+    let never = Arc::new(Mutex::new(Some(never)));
+    let server = RpcRegistry::new()
+        .register(1, move |_: DartValue| {
+            let started = started.lock().unwrap().take();
+            let never = never.lock().unwrap().take();
+            async move {
+                started.unwrap().send(()).unwrap();
+                never.unwrap().await.map_err(|_| "dropped")
+            }
+        })
+        .serve("rpc", spawn)
+        .unwrap();
+    let port = ReceivePort::new();
+
+    request(&server, 1, 1, &port, ());
+    block_on(is_started).unwrap();
+    assert_eq!(server.in_flight(), 1);
+    assert!(Isolate::new(server.port()).post((1, 1)));
+    for _ in 0..500 {
+        if server.in_flight() == 0 {
+            break;
+        }
+        thread::sleep(Duration::from_millis(2));
+    }
+    assert_eq!(server.in_flight(), 0);
+    // an aborted request is not replied to.
+    thread::sleep(Duration::from_millis(10));
+    assert_eq!(port.recv(), None);
+}
+
+/// Sends `"aborted"` when the request it is part of is dropped.
+struct OnAbort(mpsc::Sender<&'static str>);
+
+impl Drop for OnAbort {
+    fn drop(&mut self) {
+        let _ = self.0.send("aborted");
+    }
+}
+
+#[test]
+fn reused_ids() {
+    testing::install();
+    let (events, received) = mpsc::channel();
+    let events = Mutex::new(events);
+    let server = RpcRegistry::new()
+        .register(1, |n: i64| async move { Ok::<_, String>(n) })
+        .register(2, move |_: DartValue| {
+            let events = events.lock().unwrap().clone();
+            async move {
+                events.send("started").unwrap();
+                let _aborted = OnAbort(events);
+                future::pending::<Result<(), String>>().await
+            }
+        })
+        .serve("rpc", spawn)
+        .unwrap();
+    let port = ReceivePort::new();
+    let event = || received.recv_timeout(Duration::from_secs(1)).unwrap();
+
+    // the request is done once it is replied to.
+    request(&server, 1, 1, &port, 2);
+    assert_eq!(wait(&port), reply(1, 0, 2));
+    assert_eq!(server.in_flight(), 0);
+    request(&server, 1, 2, &port, ());
+    assert_eq!(event(), "started");
+    thread::sleep(Duration::from_millis(10));
+    assert_eq!(server.in_flight(), 1);
+
+    // a request with the same id cancels the one in flight.
+    request(&server, 1, 2, &port, ());
+    let mut events = [event(), event()];
+    events.sort();
+    assert_eq!(events, ["aborted", "started"]);
+    assert_eq!(server.in_flight(), 1);
+    assert!(Isolate::new(server.port()).post((1, 1)));
+    assert_eq!(event(), "aborted");
+    assert_eq!(server.in_flight(), 0);
+    assert_eq!(port.recv(), None);
+}
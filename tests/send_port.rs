@@ -0,0 +1,42 @@
+use allo_isolate::{
+    ffi::run_destructors,
+    testing::{self, ReceivePort},
+    Capability, DartValue, FromDart, IntoDart, NativePort, SendPort,
+};
+
+#[test]
+fn reply_to() {
+    testing::install();
+    let server = NativePort::new("server", |msg| {
+        let (n, reply_to) = <(i64, SendPort)>::from_dart(msg).unwrap();
+        assert!(reply_to.isolate().post(n * 2));
+    })
+    .unwrap();
+
+    let replies = ReceivePort::new();
+    let reply_to = SendPort::new(replies.port());
+    assert!(server.isolate().post((21, reply_to)));
+    assert_eq!(replies.messages(), vec![DartValue::Int(42)]);
+
+    assert_eq!(server.send_port(), SendPort::new(server.port()));
+}
+
+#[test]
+fn conversions() {
+    let port = SendPort {
+        id: 1,
+        origin_id: 2,
+    };
+    let capability = Capability { id: 3 };
+    let obj = vec![port.into_dart(), capability.into_dart()].into_dart();
+    assert_eq!(
+        <(SendPort, Capability)>::from_dart(&obj).unwrap(),
+        (port, capability)
+    );
+    assert_eq!(
+        DartValue::from_dart(&obj).unwrap(),
+        DartValue::List(vec![port.into(), capability.into()])
+    );
+    assert!(SendPort::from_dart(&capability.into_dart()).is_err());
+    unsafe { run_destructors(&obj) };
+}
@@ -0,0 +1,241 @@
+#![cfg(feature = "serde")]
+
+use allo_isolate::{
+    ffi::{DartCObject, DartCObjectType, DartTypedDataType},
+    from_dart,
+    testing::{self, ReceivePort},
+    to_dart, FromDart, IntoDart, Serde,
+};
+use serde::{Deserialize, Serialize, Serializer};
+use std::collections::BTreeMap;
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct Point {
+    x: f64,
+    y: f64,
+    #[serde(skip)]
+    _label: String,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+enum Event {
+    Quit,
+    Move(Point),
+    Resize(u32, u32),
+    Rename { name: String },
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct Frame<'a> {
+    id: u64,
+    #[serde(serialize_with = "as_bytes")]
+    pixels: &'a [u8],
+    events: Vec<Event>,
+    tags: BTreeMap<String, i32>,
+    parent: Option<u64>,
+}
+
+#[derive(Debug, PartialEq, Deserialize)]
+enum Tagged {
+    A(i32, String),
+    B { number: i32, text: String },
+}
+
+#[derive(Debug, PartialEq, Deserialize)]
+struct Blob {
+    bytes: Vec<u8>,
+    fixed: [u8; 2],
+}
+
+fn as_bytes<S: Serializer>(v: &[u8], s: S) -> Result<S::Ok, S::Error> {
+    s.serialize_bytes(v)
+}
+
+fn decode<T: FromDart>(obj: DartCObject) -> T {
+    let decoded = T::from_dart(&obj).unwrap();
+    unsafe { allo_isolate::ffi::run_destructors(&obj) };
+    decoded
+}
+
+#[test]
+fn layout() {
+    let point = Point {
+        x: 1.0,
+        y: 2.0,
+        _label: "skipped".into(),
+    };
+    assert_eq!(decode::<(f64, f64)>(to_dart(&point).unwrap()), (1.0, 2.0));
+    assert_eq!(decode::<(i32,)>(to_dart(&Event::Quit).unwrap()), (0,));
+    assert_eq!(
+        decode::<(i32, u32, u32)>(to_dart(&Event::Resize(1, 2)).unwrap()),
+        (2, 1, 2)
+    );
+    assert_eq!(
+        decode::<(i32, String)>(
+            to_dart(&Event::Rename { name: "a".into() }).unwrap()
+        ),
+        (3, "a".to_owned())
+    );
+    assert_eq!(
+        decode::<Vec<(String, i32)>>(
+            to_dart(&BTreeMap::from([("a", 1), ("b", 2)])).unwrap()
+        ),
+        vec![("a".to_owned(), 1), ("b".to_owned(), 2)]
+    );
+    assert_eq!(to_dart(&()).unwrap().ty, DartCObjectType::DartNull);
+    assert_eq!(to_dart(&None::<i32>).unwrap().ty, DartCObjectType::DartNull);
+
+    let bytes = to_dart(&Frame {
+        id: 1,
+        pixels: &[1, 2, 3],
+        events: vec![],
+        tags: BTreeMap::new(),
+        parent: None,
+    })
+    .unwrap();
+    unsafe {
+        let fields = std::slice::from_raw_parts(
+            bytes.value.as_array.values,
+            bytes.value.as_array.length as usize,
+        );
+        assert_eq!(fields.len(), 5);
+        let pixels = &*fields[1];
+        let ty = match pixels.ty {
+            DartCObjectType::DartTypedData => pixels.value.as_typed_data.ty,
+            _ => pixels.value.as_external_typed_data.ty,
+        };
+        assert_eq!(ty, DartTypedDataType::Uint8);
+    }
+    assert_eq!(
+        decode::<(u64, Vec<u8>, Vec<(i32,)>, Vec<(String, i32)>, Option<u64>)>(
+            bytes
+        )
+        .1,
+        vec![1, 2, 3]
+    );
+}
+
+#[test]
+fn post() {
+    testing::install();
+    let port = ReceivePort::new();
+    let isolate = port.isolate();
+    let frame = Frame {
+        id: 42,
+        pixels: &[0; 1024],
+        events: vec![
+            Event::Quit,
+            Event::Move(Point {
+                x: 0.0,
+                y: 1.0,
+                _label: String::new(),
+            }),
+            Event::Rename {
+                name: "frame".into(),
+            },
+        ],
+        tags: BTreeMap::from([("a".to_owned(), 1)]),
+        parent: Some(41),
+    };
+    assert!(isolate.post(Serde(&frame)));
+    assert!(isolate.post(vec![Serde(1), Serde(2)]));
+}
+
+#[test]
+fn round_trip() {
+    let frame = Frame {
+        id: u64::MAX,
+        pixels: &[1, 2, 3],
+        events: vec![
+            Event::Quit,
+            Event::Move(Point {
+                x: 0.5,
+                y: -1.0,
+                _label: String::new(),
+            }),
+            Event::Resize(640, 480),
+            Event::Rename {
+                name: "frame".into(),
+            },
+        ],
+        tags: BTreeMap::from([("a".to_owned(), 1), ("b".to_owned(), -2)]),
+        parent: Some(41),
+    };
+    let obj = to_dart(&frame).unwrap();
+    assert_eq!(from_dart::<Frame>(&obj).unwrap(), frame);
+    unsafe { allo_isolate::ffi::run_destructors(&obj) };
+
+    let obj = to_dart(&(u128::MAX, i128::MIN, 'ä', "borrowed")).unwrap();
+    assert_eq!(
+        from_dart::<(u128, i128, char, &str)>(&obj).unwrap(),
+        (u128::MAX, i128::MIN, 'ä', "borrowed")
+    );
+    unsafe { allo_isolate::ffi::run_destructors(&obj) };
+
+    // a `Uint8List` is a sequence of `u8`s too.
+    let obj = (vec![1u8, 2].into_dart(), vec![3u8, 4]).into_dart();
+    assert_eq!(
+        from_dart::<Blob>(&obj).unwrap(),
+        Blob {
+            bytes: vec![1, 2],
+            fixed: [3, 4],
+        }
+    );
+    unsafe { allo_isolate::ffi::run_destructors(&obj) };
+}
+
+#[test]
+fn from_into_dart() {
+    // values sent by `IntoDart` are read the same way.
+    let obj = (vec![1i32, 2, 3], vec![0.5f32], "a".to_owned(), None::<i32>)
+        .into_dart();
+    assert_eq!(
+        from_dart::<(Vec<i64>, Vec<f64>, String, Option<i32>)>(&obj).unwrap(),
+        (vec![1, 2, 3], vec![0.5], "a".to_owned(), None)
+    );
+    unsafe { allo_isolate::ffi::run_destructors(&obj) };
+}
+
+#[test]
+fn error_path() {
+    let obj = (
+        1,
+        vec![0u8].into_dart(),
+        vec![
+            vec![0.into_dart()].into_dart(),
+            vec![3.into_dart(), 1.into_dart()].into_dart(),
+        ],
+        Vec::<DartCObject>::new(),
+        None::<i32>,
+    )
+        .into_dart();
+    let err = from_dart::<Frame>(&obj).unwrap_err();
+    assert_eq!(err.path(), ".events[1].name");
+    assert_eq!(
+        err.to_string(),
+        "invalid type: integer `1`, expected a string at `.events[1].name`"
+    );
+    unsafe { allo_isolate::ffi::run_destructors(&obj) };
+
+    let obj = vec![vec!["a".into_dart(), "b".into_dart()]].into_dart();
+    let err = from_dart::<BTreeMap<String, i32>>(&obj).unwrap_err();
+    assert_eq!(err.path(), "[0][1]");
+    unsafe { allo_isolate::ffi::run_destructors(&obj) };
+
+    // the variant index is the first item of the list.
+    let obj = (0, 5, "a").into_dart();
+    assert_eq!(
+        from_dart::<Tagged>(&obj).unwrap(),
+        Tagged::A(5, "a".to_owned())
+    );
+    unsafe { allo_isolate::ffi::run_destructors(&obj) };
+    let obj = (0, 5, 7).into_dart();
+    let err = from_dart::<Tagged>(&obj).unwrap_err();
+    assert_eq!(err.path(), "[2]");
+    let obj = (1, 5, 7).into_dart();
+    let err = from_dart::<Tagged>(&obj).unwrap_err();
+    assert_eq!(err.path(), ".text");
+
+    let err = from_dart::<String>(&1.into_dart()).unwrap_err();
+    assert_eq!(err.path(), ".");
+}
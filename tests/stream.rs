@@ -0,0 +1,63 @@
+#![cfg(feature = "futures")]
+
+use allo_isolate::{
+    testing::{self, ReceivePort},
+    Isolate, PostError,
+};
+use futures::{executor::block_on, stream, SinkExt, StreamExt};
+use std::cell::Cell;
+
+#[test]
+fn forward_stream() {
+    testing::install();
+    let port = ReceivePort::new();
+    let isolate = port.isolate();
+    assert_eq!(
+        block_on(isolate.forward_stream(stream::iter(0..100))),
+        Ok(())
+    );
+    assert_eq!(
+        block_on(isolate.forward_stream_with_end(
+            stream::iter(vec![vec![1u8; 10]; 10]),
+            ()
+        )),
+        Ok(())
+    );
+
+    // stops at the first item once the port is closed
+    let polled = Cell::new(0);
+    let items = stream::iter(0..100).inspect(|_| polled.set(polled.get() + 1));
+    let closed = Isolate::new(0);
+    assert_eq!(
+        block_on(closed.forward_stream_with_end(items, "done")),
+        Err(PostError::PortClosed)
+    );
+    assert_eq!(polled.get(), 1);
+}
+
+#[test]
+fn sink() {
+    testing::install();
+    let port = ReceivePort::new();
+    let isolate = port.isolate();
+    let mut sink = isolate.sink();
+    assert_eq!(block_on(sink.send(String::from("one"))), Ok(()));
+    assert_eq!(
+        block_on(
+            stream::iter(vec!["two".to_owned(); 10])
+                .map(Ok)
+                .forward(&mut sink)
+        ),
+        Ok(())
+    );
+
+    // the error is kept once the port is closed
+    let mut closed = Isolate::new(0).sink();
+    let mut items = stream::iter(0..100).map(Ok);
+    assert_eq!(
+        block_on(closed.send_all(&mut items)),
+        Err(PostError::PortClosed)
+    );
+    assert_eq!(block_on(closed.send(1)), Err(PostError::PortClosed));
+    assert_eq!(block_on(items.next()), Some(Ok(1)));
+}
@@ -0,0 +1,111 @@
+use allo_isolate::{
+    testing::{self, ReceivePort},
+    DartValue, NativeHandle, PostError, TypedData, ZeroCopyBuffer,
+};
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+};
+
+#[test]
+fn records_messages() {
+    testing::install();
+    let port = ReceivePort::new();
+    let isolate = port.isolate();
+    assert!(isolate.post(42i32));
+    assert!(isolate.post(u64::MAX));
+    assert!(isolate.post((true, 0.5f64, "Dart", None::<i32>)));
+    assert!(isolate.post(vec![1u16, 2]));
+    assert!(isolate.post(ZeroCopyBuffer(vec![1.0f32, 2.0])));
+    assert_eq!(
+        port.messages(),
+        vec![
+            DartValue::Int(42),
+            DartValue::Int(-1),
+            DartValue::List(vec![
+                DartValue::Bool(true),
+                DartValue::Double(0.5),
+                DartValue::String(String::from("Dart")),
+                DartValue::Null,
+            ]),
+            DartValue::TypedData(TypedData::Uint16(vec![1, 2])),
+            DartValue::TypedData(TypedData::Float32(vec![1.0, 2.0])),
+        ]
+    );
+    assert_eq!(port.recv(), None);
+}
+
+#[test]
+fn finalizers() {
+    testing::install();
+    let port = ReceivePort::new();
+    let isolate = port.isolate();
+    assert!(isolate.post(vec![ZeroCopyBuffer(vec![1u8; 8]); 2]));
+    assert!(isolate.post(ZeroCopyBuffer(vec![2u8; 8])));
+
+    // the external typed data stays alive until its message is collected.
+    assert!(port.recv().is_some());
+    assert!(testing::gc() >= 2);
+    assert_eq!(
+        port.recv(),
+        Some(DartValue::TypedData(TypedData::Uint8(vec![2; 8])))
+    );
+}
+
+#[test]
+fn closed_port() {
+    testing::install();
+    let port = ReceivePort::new();
+    let isolate = port.isolate();
+    assert!(isolate.post(ZeroCopyBuffer(vec![1u8; 8])));
+    assert!(!port.is_closed());
+
+    // the pending message is dropped together with the port.
+    assert!(port.close());
+    assert!(port.is_closed());
+    assert!(!port.close());
+    assert_eq!(port.recv(), None);
+    assert_eq!(
+        isolate.try_post(ZeroCopyBuffer(vec![1u8; 8])),
+        Err(PostError::PortClosed)
+    );
+}
+
+#[test]
+fn native_handles() {
+    struct Tracked(Arc<AtomicUsize>);
+    impl Drop for Tracked {
+        fn drop(&mut self) {
+            self.0.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    testing::install();
+    let dropped = Arc::new(AtomicUsize::new(0));
+    let port = ReceivePort::new();
+    let isolate = port.isolate();
+    assert!(isolate.post(NativeHandle::new(Tracked(dropped.clone()))));
+    assert!(matches!(
+        port.recv(),
+        Some(DartValue::NativePointer { size, .. })
+            if size as usize == std::mem::size_of::<Tracked>()
+    ));
+    assert_eq!(dropped.load(Ordering::SeqCst), 0);
+    testing::gc();
+    assert_eq!(dropped.load(Ordering::SeqCst), 1);
+
+    // dropped right away when the port is closed.
+    assert!(port.close());
+    assert!(!isolate.post(NativeHandle::new(Tracked(dropped.clone()))));
+    assert_eq!(dropped.load(Ordering::SeqCst), 2);
+}
+
+#[test]
+fn post_integer() {
+    testing::install();
+    let port = ReceivePort::new();
+    assert!(port.isolate().post_integer(-5));
+    assert_eq!(port.recv(), Some(DartValue::Int(-5)));
+    port.close();
+    assert!(!port.isolate().post_integer(1));
+}
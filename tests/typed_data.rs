@@ -0,0 +1,101 @@
+use allo_isolate::{
+    ffi::{run_destructors, DartTypedDataType},
+    testing::{self, ReceivePort},
+    ByteData, DartValue, Float32x4, Float64x2, FromDart, FromDartError,
+    Int32x4, IntoDart, TypedData, Uint8Clamped, ZeroCopyBuffer,
+};
+
+#[test]
+fn simd() {
+    testing::install();
+    let port = ReceivePort::new();
+    let isolate = port.isolate();
+    let floats = vec![Float32x4([1.0, 2.0, 3.0, 4.0]); 3];
+    let ints = vec![Int32x4([1, -2, 3, -4]); 2];
+    let doubles = [Float64x2([0.5, -0.5])];
+    assert!(isolate.post(floats.clone()));
+    assert!(isolate.post(ZeroCopyBuffer(floats.clone())));
+    assert!(isolate.post(ints.clone()));
+    assert!(isolate.post(ZeroCopyBuffer(doubles)));
+    assert_eq!(
+        port.messages(),
+        vec![
+            DartValue::TypedData(TypedData::Float32x4(floats.clone())),
+            DartValue::TypedData(TypedData::Float32x4(floats.clone())),
+            DartValue::TypedData(TypedData::Int32x4(ints.clone())),
+            DartValue::TypedData(TypedData::Float64x2(doubles.to_vec())),
+        ]
+    );
+    testing::gc();
+
+    let obj =
+        (floats.clone(), ZeroCopyBuffer(ints.clone()), doubles).into_dart();
+    assert_eq!(
+        <(Vec<Float32x4>, Vec<Int32x4>, [Float64x2; 1])>::from_dart(&obj)
+            .unwrap(),
+        (floats, ints, doubles)
+    );
+    unsafe { run_destructors(&obj) };
+}
+
+#[test]
+fn typed_data_types() {
+    // the discriminants of `Dart_TypedData_Type` in dart_native_api.h
+    assert_eq!(DartTypedDataType::Int32x4 as i32, 12);
+    assert_eq!(DartTypedDataType::Float32x4 as i32, 13);
+    assert_eq!(DartTypedDataType::Float64x2 as i32, 14);
+    assert_eq!(DartTypedDataType::Invalid as i32, 15);
+}
+
+#[test]
+fn bytes() {
+    testing::install();
+    let port = ReceivePort::new();
+    let isolate = port.isolate();
+    let pixels = Uint8Clamped(vec![0, 128, 255]);
+    let packet = ByteData(vec![1, 2, 3, 4]);
+    assert!(isolate.post(pixels.clone()));
+    assert!(isolate.post(ZeroCopyBuffer(pixels.clone())));
+    assert!(isolate.post(packet.clone()));
+    assert!(isolate.post(ZeroCopyBuffer(ByteData::default())));
+    assert_eq!(
+        port.messages(),
+        vec![
+            DartValue::from(pixels.clone()),
+            DartValue::from(pixels.clone()),
+            DartValue::from(packet.clone()),
+            DartValue::from(ByteData::default()),
+        ]
+    );
+    testing::gc();
+
+    let obj = (pixels.clone(), ZeroCopyBuffer(packet.clone())).into_dart();
+    assert_eq!(
+        <(Uint8Clamped, ByteData)>::from_dart(&obj).unwrap(),
+        (pixels.clone(), packet)
+    );
+    // neither of them is a `Uint8List`.
+    let items = unsafe {
+        std::slice::from_raw_parts(
+            obj.value.as_array.values,
+            obj.value.as_array.length as usize,
+        )
+    };
+    for (item, found) in items
+        .iter()
+        .zip([DartTypedDataType::Uint8Clamped, DartTypedDataType::ByteData])
+    {
+        assert_eq!(
+            Vec::<u8>::from_dart(unsafe { &**item }).unwrap_err(),
+            FromDartError::UnexpectedTypedDataType {
+                expected: DartTypedDataType::Uint8,
+                found,
+            }
+        );
+    }
+    assert_eq!(
+        DartValue::from(pixels).to_string(),
+        "Uint8ClampedList[0, 128, 255]"
+    );
+    unsafe { run_destructors(&obj) };
+}
@@ -0,0 +1,78 @@
+use allo_isolate::{
+    ffi::{run_destructors, DartCObjectType},
+    DartValue, FromDart, IntoDart, TypedData,
+};
+
+fn round_trip(value: DartValue) -> DartValue {
+    let obj = value.into_dart();
+    let decoded = DartValue::from_dart(&obj).unwrap();
+    unsafe { run_destructors(&obj) };
+    decoded
+}
+
+#[test]
+fn conversions() {
+    let value = DartValue::List(vec![
+        DartValue::Null,
+        DartValue::from(true),
+        DartValue::from(i64::MIN),
+        DartValue::from(0.5),
+        DartValue::from("Dart"),
+        DartValue::from(vec![1.0f32, 2.0]),
+        DartValue::from(vec![DartValue::from(Some(1)), None::<i32>.into()]),
+        DartValue::SendPort {
+            id: 1,
+            origin_id: 2,
+        },
+        DartValue::Capability(3),
+        DartValue::NativePointer { ptr: 4, size: 8 },
+    ]);
+    assert_eq!(round_trip(value.clone()), value);
+
+    let obj = DartValue::Capability(3).into_dart();
+    assert_eq!(obj.ty, DartCObjectType::DartCapability);
+
+    // everything `IntoDart` produces can be decoded.
+    let obj = (vec![1i32, 2], "a", 'a', None::<bool>).into_dart();
+    assert_eq!(
+        DartValue::from_dart(&obj).unwrap(),
+        DartValue::List(vec![
+            DartValue::TypedData(TypedData::Int32(vec![1, 2])),
+            DartValue::from("a"),
+            DartValue::from(97),
+            DartValue::Null,
+        ])
+    );
+    unsafe { run_destructors(&obj) };
+}
+
+#[test]
+fn display() {
+    let value = DartValue::List(vec![
+        DartValue::from("frame"),
+        DartValue::from(vec![DartValue::from(1.0), DartValue::Null]),
+        DartValue::List(vec![]),
+        DartValue::from(vec![1u8, 2]),
+        DartValue::SendPort {
+            id: 1,
+            origin_id: 2,
+        },
+    ]);
+    assert_eq!(
+        value.to_string(),
+        r#"["frame", [1.0, null], [], Uint8List[1, 2], SendPort(id: 1, origin: 2)]"#
+    );
+    assert_eq!(
+        format!("{:#}", value),
+        r#"[
+  "frame",
+  [
+    1.0,
+    null,
+  ],
+  [],
+  Uint8List[1, 2],
+  SendPort(id: 1, origin: 2),
+]"#
+    );
+}